@@ -0,0 +1,26 @@
+-- existing events get their sequences backfilled in insertion order
+ALTER TABLE user_events ADD COLUMN IF NOT EXISTS sequence BIGINT;
+
+UPDATE user_events AS e SET sequence = s.sequence
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY id) AS sequence
+    FROM user_events
+) AS s
+WHERE e.id = s.id AND e.sequence IS NULL;
+
+ALTER TABLE user_events ALTER COLUMN sequence SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS user_events_sequence ON user_events (user_id, sequence);
+
+ALTER TABLE journal_events ADD COLUMN IF NOT EXISTS sequence BIGINT;
+
+UPDATE journal_events AS e SET sequence = s.sequence
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY journal_id ORDER BY id) AS sequence
+    FROM journal_events
+) AS s
+WHERE e.id = s.id AND e.sequence IS NULL;
+
+ALTER TABLE journal_events ALTER COLUMN sequence SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS journal_events_sequence ON journal_events (journal_id, sequence);
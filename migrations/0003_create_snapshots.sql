@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS snapshots (
+    aggregate_id UUID NOT NULL,
+    aggregate_type SMALLINT NOT NULL,
+    event_filter BIGINT NOT NULL,
+    schema_version SMALLINT NOT NULL,
+    last_event_id BIGINT NOT NULL,
+    payload BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (aggregate_id, aggregate_type, event_filter)
+);
@@ -0,0 +1,14 @@
+CREATE INDEX IF NOT EXISTS user_events_replay
+ON user_events (user_id, event_type, created_at);
+
+CREATE INDEX IF NOT EXISTS journal_events_replay
+ON journal_events (journal_id, event_type, created_at);
+
+CREATE INDEX IF NOT EXISTS auth_events_session
+ON auth_events (session_id, created_at);
+
+CREATE INDEX IF NOT EXISTS username_events_username
+ON username_events (username, created_at);
+
+CREATE INDEX IF NOT EXISTS username_events_user
+ON username_events (user_id, created_at);
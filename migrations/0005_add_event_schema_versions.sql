@@ -0,0 +1,13 @@
+-- every payload is tagged with the layout version of the event enum it was encoded with
+ALTER TABLE user_events ADD COLUMN IF NOT EXISTS schema_version SMALLINT NOT NULL DEFAULT 1;
+
+ALTER TABLE user_events ALTER COLUMN schema_version DROP DEFAULT;
+
+ALTER TABLE journal_events ADD COLUMN IF NOT EXISTS schema_version SMALLINT NOT NULL DEFAULT 1;
+
+-- account creations from before the account id was stored are the variant tag followed
+-- directly by the name, instead of a 16 byte id (length prefix 0x10) and then the name
+UPDATE journal_events SET schema_version = 0
+WHERE event_type = 3 AND (get_byte(payload, 1) <> 16 OR length(payload) = 18);
+
+ALTER TABLE journal_events ALTER COLUMN schema_version DROP DEFAULT;
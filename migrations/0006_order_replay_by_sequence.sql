@@ -0,0 +1,30 @@
+-- snapshots track the last replayed sequence instead of the last event id,
+-- existing ones recorded ids and have to be rebuilt
+DELETE FROM snapshots;
+
+ALTER TABLE snapshots RENAME COLUMN last_event_id TO last_sequence;
+
+DROP INDEX IF EXISTS user_events_replay;
+
+CREATE INDEX IF NOT EXISTS user_events_replay
+ON user_events (user_id, event_type, sequence);
+
+DROP INDEX IF EXISTS journal_events_replay;
+
+CREATE INDEX IF NOT EXISTS journal_events_replay
+ON journal_events (journal_id, event_type, sequence);
+
+DROP INDEX IF EXISTS auth_events_session;
+
+CREATE INDEX IF NOT EXISTS auth_events_session
+ON auth_events (session_id, id);
+
+DROP INDEX IF EXISTS username_events_username;
+
+CREATE INDEX IF NOT EXISTS username_events_username
+ON username_events (username, id);
+
+DROP INDEX IF EXISTS username_events_user;
+
+CREATE INDEX IF NOT EXISTS username_events_user
+ON username_events (user_id, id);
@@ -0,0 +1,10 @@
+-- user events about a journal keep its id next to the payload, so a journal's tenants can be
+-- read without replaying every user's invites. the id is the first field of each of those
+-- variants, which postcard writes after the variant tag and a 16 byte length prefix
+ALTER TABLE user_events ADD COLUMN IF NOT EXISTS journal_id UUID;
+
+UPDATE user_events SET journal_id = encode(substring(payload FROM 3 FOR 16), 'hex')::uuid
+WHERE event_type IN (4, 5, 6, 7, 8, 9, 11) AND journal_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS user_events_journal
+ON user_events (journal_id, user_id, sequence) WHERE journal_id IS NOT NULL;
@@ -0,0 +1,268 @@
+use super::return_types::KnownErrors;
+use crate::event_sourcing::journal::{JournalEventType, JournalState, Permissions};
+use crate::event_sourcing::user::{self, UserState};
+use leptos::prelude::ServerFnError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// the user events that decide who can access which journal
+const ACCESS_EVENTS: [user::UserEventType; 6] = [
+    user::UserEventType::CreatedJournal,
+    user::UserEventType::InvitedToJournal,
+    user::UserEventType::AcceptedJournalInvite,
+    user::UserEventType::DeclinedJournalInvite,
+    user::UserEventType::RemovedFromJournal,
+    user::UserEventType::TenantPermissionsUpdated,
+];
+
+/// proof that a user held the checked permissions on a journal.
+/// it can only be made by `authorize` or `check`
+pub struct JournalAccess {
+    pub user_id: Uuid,
+    pub journal_id: Uuid,
+    owner: Uuid,
+    permissions: Permissions,
+    /// the version of the user's stream the access was read at, pushing to the user's
+    /// stream with it fails if their access changed since
+    pub user_sequence: i64,
+    /// the version of the journal's stream `authorize` found it not deleted at. `check`
+    /// doesn't read the journal and leaves it at 0
+    pub journal_sequence: i64,
+}
+
+impl JournalAccess {
+    /// everything the user may do in the journal, owners hold every permission
+    pub fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    pub fn owner(&self) -> Uuid {
+        self.owner
+    }
+
+    pub fn is_owner(&self) -> bool {
+        self.owner == self.user_id
+    }
+
+    /// fails unless the user holds every one of `permissions`. used when a user hands
+    /// permissions on to someone else, since nobody can grant more than they have
+    pub fn require(&self, permissions: Permissions) -> Result<(), KnownErrors> {
+        if self.permissions.contains(permissions) {
+            Ok(())
+        } else {
+            Err(KnownErrors::PermissionError {
+                required_permissions: permissions.difference(self.permissions),
+            })
+        }
+    }
+}
+
+/// the user's permissions on the journal, or `None` if they can't access it at all
+pub fn effective_permissions(user_state: &UserState, journal_id: &Uuid) -> Option<Permissions> {
+    if user_state.owned_journals.contains(journal_id) {
+        return Some(Permissions::all());
+    }
+
+    user_state
+        .accepted_journal_invites
+        .get(journal_id)
+        .map(|tenant_info| tenant_info.tenant_permissions)
+}
+
+/// checks `required` against a user state that was built from `ACCESS_EVENTS`
+pub fn check(
+    user_id: &Uuid,
+    user_state: &UserState,
+    journal_id: &Uuid,
+    required: Permissions,
+) -> Result<JournalAccess, KnownErrors> {
+    let owner = if user_state.owned_journals.contains(journal_id) {
+        Some(*user_id)
+    } else {
+        user_state
+            .accepted_journal_invites
+            .get(journal_id)
+            .map(|tenant_info| tenant_info.journal_owner)
+    };
+
+    match (owner, effective_permissions(user_state, journal_id)) {
+        (Some(owner), Some(permissions)) if permissions.contains(required) => Ok(JournalAccess {
+            user_id: *user_id,
+            journal_id: *journal_id,
+            owner,
+            permissions,
+            user_sequence: user_state.sequence,
+            journal_sequence: 0,
+        }),
+        _ => Err(KnownErrors::PermissionError {
+            required_permissions: required,
+        }),
+    }
+}
+
+/// the user's state built from `ACCESS_EVENTS`
+pub async fn access_state(user_id: &Uuid, pool: &PgPool) -> Result<UserState, ServerFnError> {
+    UserState::build(user_id, ACCESS_EVENTS.to_vec(), pool).await
+}
+
+/// replays the user's journal access and fails with `KnownErrors::PermissionError`
+/// unless they hold `required` on the journal, or with `KnownErrors::InvalidJournal`
+/// if the journal has been deleted
+pub async fn authorize(
+    user_id: &Uuid,
+    journal_id: &Uuid,
+    required: Permissions,
+    pool: &PgPool,
+) -> Result<JournalAccess, ServerFnError> {
+    let user_state = access_state(user_id, pool).await?;
+
+    let mut access = match check(user_id, &user_state, journal_id, required) {
+        Ok(access) => access,
+        Err(e) => return Err(ServerFnError::ServerError(e.to_string()?)),
+    };
+
+    let journal_state = JournalState::build(
+        journal_id,
+        vec![JournalEventType::Deleted, JournalEventType::Restored],
+        pool,
+    )
+    .await?;
+
+    if journal_state.deleted {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidJournal.to_string()?,
+        ));
+    }
+
+    access.journal_sequence = journal_state.sequence;
+
+    Ok(access)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_sourcing::user::UserEvent;
+
+    fn state(events: Vec<UserEvent>) -> UserState {
+        let mut state = UserState::default();
+        for event in events {
+            state.apply(event);
+        }
+        state
+    }
+
+    fn invited(journal_id: Uuid, owner: Uuid, permissions: Permissions) -> UserEvent {
+        UserEvent::InvitedToJournal {
+            id: journal_id,
+            permissions,
+            inviting_user: owner,
+            owner,
+        }
+    }
+
+    fn is_permission_error(result: Result<JournalAccess, KnownErrors>, expected: Permissions) {
+        assert!(matches!(
+            result,
+            Err(KnownErrors::PermissionError { required_permissions }) if required_permissions == expected
+        ));
+    }
+
+    #[test]
+    fn owner_holds_every_permission() {
+        let (user_id, journal_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let user_state = state(vec![UserEvent::CreatedJournal { id: journal_id }]);
+
+        let Ok(access) = check(&user_id, &user_state, &journal_id, Permissions::all()) else {
+            panic!("owners can do anything");
+        };
+
+        assert!(access.is_owner());
+        assert_eq!(access.owner(), user_id);
+        assert_eq!(access.permissions(), Permissions::all());
+    }
+
+    #[test]
+    fn tenant_is_limited_to_granted_permissions() {
+        let (user_id, owner, journal_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let granted = Permissions::READ | Permissions::APPENDTRANSACTION;
+        let user_state = state(vec![
+            invited(journal_id, owner, granted),
+            UserEvent::AcceptedJournalInvite { id: journal_id },
+        ]);
+
+        let Ok(access) = check(&user_id, &user_state, &journal_id, Permissions::READ) else {
+            panic!("the tenant was granted read");
+        };
+
+        assert!(!access.is_owner());
+        assert_eq!(access.owner(), owner);
+        assert_eq!(access.permissions(), granted);
+        assert!(access.require(granted).is_ok());
+        assert!(
+            access.require(Permissions::READ | Permissions::INVITE)
+                == Err(KnownErrors::PermissionError {
+                    required_permissions: Permissions::INVITE,
+                })
+        );
+
+        is_permission_error(
+            check(&user_id, &user_state, &journal_id, Permissions::ADDACCOUNT),
+            Permissions::ADDACCOUNT,
+        );
+    }
+
+    #[test]
+    fn tenant_permissions_can_be_changed() {
+        let (user_id, journal_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let user_state = state(vec![
+            invited(journal_id, Uuid::new_v4(), Permissions::READ),
+            UserEvent::AcceptedJournalInvite { id: journal_id },
+            UserEvent::TenantPermissionsUpdated {
+                id: journal_id,
+                permissions: Permissions::READ | Permissions::ADDACCOUNT,
+            },
+        ]);
+
+        assert!(check(&user_id, &user_state, &journal_id, Permissions::ADDACCOUNT).is_ok());
+    }
+
+    #[test]
+    fn pending_invite_grants_nothing() {
+        let (user_id, journal_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let user_state = state(vec![invited(journal_id, Uuid::new_v4(), Permissions::READ)]);
+
+        assert_eq!(effective_permissions(&user_state, &journal_id), None);
+        is_permission_error(
+            check(&user_id, &user_state, &journal_id, Permissions::READ),
+            Permissions::READ,
+        );
+    }
+
+    #[test]
+    fn revoked_tenant_loses_access() {
+        let (user_id, journal_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let user_state = state(vec![
+            invited(journal_id, Uuid::new_v4(), Permissions::all()),
+            UserEvent::AcceptedJournalInvite { id: journal_id },
+            UserEvent::RemovedFromJournal { id: journal_id },
+        ]);
+
+        assert_eq!(effective_permissions(&user_state, &journal_id), None);
+        is_permission_error(
+            check(&user_id, &user_state, &journal_id, Permissions::READ),
+            Permissions::READ,
+        );
+    }
+
+    #[test]
+    fn access_is_per_journal() {
+        let (user_id, journal_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let user_state = state(vec![UserEvent::CreatedJournal { id: journal_id }]);
+
+        is_permission_error(
+            check(&user_id, &user_state, &Uuid::new_v4(), Permissions::READ),
+            Permissions::READ,
+        );
+    }
+}
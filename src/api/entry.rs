@@ -0,0 +1,510 @@
+use super::money;
+use super::return_types::KnownErrors;
+use crate::event_sourcing::currency::Currency;
+use crate::event_sourcing::journal::{BalanceUpdate, JournalEvent, JournalState, Transaction};
+use crate::event_sourcing::unit_of_work::UnitOfWork;
+use chrono::{NaiveDate, Utc};
+use leptos::prelude::ServerFnError;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// what a transaction form submits, the vectors have one element per row
+pub struct EntryForm {
+    pub description: String,
+    pub effective_date: NaiveDate,
+    /// strings rather than ids, so rows left on "select account..." still submit
+    pub account_ids: Vec<String>,
+    pub balance_add_cents: Vec<String>,
+    pub balance_remove_cents: Vec<String>,
+    pub memos: Vec<String>,
+}
+
+/// validates a submitted entry against the journal and queues it on `unit_of_work`, together
+/// with an FX gain/loss account if this is the first exchange difference in its currency.
+/// `journal_state` needs the account, archive and exchange rate events. returns the new entry's id
+pub fn push_entry(
+    form: EntryForm,
+    author: Uuid,
+    corrects: Option<Uuid>,
+    journal_state: &JournalState,
+    unit_of_work: &mut UnitOfWork,
+) -> Result<Uuid, ServerFnError> {
+    let mut updates: Vec<BalanceUpdate> = Vec::new();
+    // every currency has to balance on its own
+    let mut balance_changes: HashMap<Currency, i64> = HashMap::new();
+    let mut seen_accounts: HashSet<Uuid> = HashSet::new();
+
+    // every row has an account and both amount fields, only a crafted post can lack them
+    if form.account_ids.len() != form.balance_add_cents.len()
+        || form.balance_remove_cents.len() != form.balance_add_cents.len()
+    {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidInput.to_string()?,
+        ));
+    }
+
+    for i in 0..form.balance_add_cents.len() {
+        // rows are numbered from 1, the way they're shown in the form
+        let row = i + 1;
+
+        if form.balance_add_cents[i].trim().is_empty()
+            && form.balance_remove_cents[i].trim().is_empty()
+        {
+            continue;
+        }
+
+        // deleted accounts are gone from the state, so they're unknown as well
+        let Some((account_id, account)) = Uuid::try_parse(form.account_ids[i].trim())
+            .ok()
+            .and_then(|id| journal_state.accounts.get(&id).map(|account| (id, account)))
+        else {
+            return Err(ServerFnError::ServerError(
+                KnownErrors::UnknownAccount { row }.to_string()?,
+            ));
+        };
+
+        // one line per account, so each line's memo stays with its amount
+        if !seen_accounts.insert(account_id) {
+            return Err(ServerFnError::ServerError(
+                KnownErrors::DuplicateAccount { row }.to_string()?,
+            ));
+        }
+
+        if account.archived {
+            return Err(ServerFnError::ServerError(
+                KnownErrors::AccountArchived { row }.to_string()?,
+            ));
+        }
+
+        let add_amt = money::parse_row_amount(&form.balance_add_cents[i], row, account.currency)?;
+
+        let remove_amt =
+            money::parse_row_amount(&form.balance_remove_cents[i], row, account.currency)?;
+
+        let Some(account_sum) = add_amt.checked_sub(remove_amt) else {
+            return Err(ServerFnError::ServerError(
+                KnownErrors::AmountOverflow { row }.to_string()?,
+            ));
+        };
+
+        if account_sum != 0 {
+            let total_balance_change = balance_changes.entry(account.currency).or_default();
+            let Some(new_total) = total_balance_change.checked_add(account_sum) else {
+                return Err(ServerFnError::ServerError(
+                    KnownErrors::AmountOverflow { row }.to_string()?,
+                ));
+            };
+            *total_balance_change = new_total;
+            let memo = form
+                .memos
+                .get(i)
+                .map(|memo| memo.trim())
+                .filter(|memo| !memo.is_empty())
+                .map(str::to_string);
+
+            updates.push(BalanceUpdate {
+                account_id,
+                changed_by: account_sum,
+                memo,
+            });
+        }
+    }
+
+    if updates.is_empty() {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidInput.to_string()?,
+        ));
+    }
+
+    // currencies that don't balance on their own, in the order they were entered.
+    // the first one is what the others are converted into
+    let mut unbalanced: Vec<(Currency, i64)> = Vec::new();
+    for update in &updates {
+        if let Some(account) = journal_state.accounts.get(&update.account_id)
+            && let Some(&total) = balance_changes.get(&account.currency)
+            && total != 0
+            && !unbalanced.iter().any(|(c, _)| *c == account.currency)
+        {
+            unbalanced.push((account.currency, total));
+        }
+    }
+
+    let mut exchange_rates = Vec::new();
+
+    if let Some(&(settlement, settlement_total)) = unbalanced.first() {
+        if unbalanced.len() == 1 {
+            return Err(ServerFnError::ServerError(
+                KnownErrors::BalanceMismatch {
+                    attempted_transaction: updates,
+                    currency: settlement,
+                }
+                .to_string()?,
+            ));
+        }
+
+        let mut difference = i128::from(settlement_total);
+        // each converted line may be rounded to its currency's minor unit by up to one
+        // unit of the settlement currency, anything more is a wrong amount
+        let mut rounding_allowance: i128 = 0;
+
+        for &(currency, total) in &unbalanced[1..] {
+            let Some(rate) = journal_state.exchange_rate(currency, settlement) else {
+                return Err(ServerFnError::ServerError(
+                    KnownErrors::MissingExchangeRate {
+                        from: currency,
+                        to: settlement,
+                    }
+                    .to_string()?,
+                ));
+            };
+
+            let Some(converted) = rate.convert(total, currency) else {
+                return Err(ServerFnError::ServerError(
+                    KnownErrors::BalanceMismatch {
+                        attempted_transaction: updates,
+                        currency: settlement,
+                    }
+                    .to_string()?,
+                ));
+            };
+
+            difference += i128::from(converted);
+            rounding_allowance += updates
+                .iter()
+                .filter(|update| {
+                    journal_state
+                        .accounts
+                        .get(&update.account_id)
+                        .is_some_and(|account| account.currency == currency)
+                })
+                .count() as i128;
+            exchange_rates.push(rate);
+        }
+
+        let difference = match i64::try_from(difference) {
+            Ok(d) if i128::from(d).abs() <= rounding_allowance => d,
+            _ => {
+                return Err(ServerFnError::ServerError(
+                    KnownErrors::BalanceMismatch {
+                        attempted_transaction: updates,
+                        currency: settlement,
+                    }
+                    .to_string()?,
+                ));
+            }
+        };
+
+        if difference != 0 {
+            let fx_account = match journal_state.fx_accounts.get(&settlement) {
+                Some(id) => *id,
+                None => {
+                    let id = Uuid::new_v4();
+                    unit_of_work.push_journal(
+                        journal_state.id,
+                        journal_state.sequence,
+                        JournalEvent::CreatedFxAccount {
+                            id,
+                            currency: settlement,
+                        },
+                    );
+                    id
+                }
+            };
+
+            // a line the user already entered for the fx account takes the difference too
+            match updates.iter_mut().find(|u| u.account_id == fx_account) {
+                Some(update) => {
+                    let Some(changed_by) = update.changed_by.checked_sub(difference) else {
+                        let row = form
+                            .account_ids
+                            .iter()
+                            .position(|id| Uuid::try_parse(id.trim()).ok() == Some(fx_account))
+                            .map_or(0, |i| i + 1);
+                        return Err(ServerFnError::ServerError(
+                            KnownErrors::AmountOverflow { row }.to_string()?,
+                        ));
+                    };
+                    update.changed_by = changed_by;
+                }
+                None => updates.push(BalanceUpdate {
+                    account_id: fx_account,
+                    changed_by: -difference,
+                    memo: None,
+                }),
+            }
+        }
+    }
+
+    let id = Uuid::new_v4();
+
+    unit_of_work.push_journal(
+        journal_state.id,
+        journal_state.sequence,
+        JournalEvent::AddedEntry {
+            transaction: Transaction {
+                id,
+                author,
+                description: form.description.trim().to_string(),
+                effective_date: form.effective_date,
+                updates,
+                exchange_rates,
+                corrects,
+            },
+        },
+    );
+
+    Ok(id)
+}
+
+/// queues the reversal of `original` on `unit_of_work`, followed by the entry that cancels
+/// it out. that's dated today, or on the original's date if it's still in the future, so it
+/// never takes effect before the entry it reverses. returns the offsetting entry's id
+pub fn push_reversal(
+    original: &Transaction,
+    reason: String,
+    author: Uuid,
+    journal_state: &JournalState,
+    unit_of_work: &mut UnitOfWork,
+) -> Uuid {
+    let offset = Uuid::new_v4();
+
+    unit_of_work.push_journal(
+        journal_state.id,
+        journal_state.sequence,
+        JournalEvent::ReversedEntry {
+            entry_id: original.id,
+            reason,
+            offset: Some(offset),
+        },
+    );
+
+    unit_of_work.push_journal(
+        journal_state.id,
+        journal_state.sequence,
+        JournalEvent::AddedEntry {
+            transaction: Transaction {
+                id: offset,
+                author,
+                description: format!("Reversal of {}", original.description),
+                effective_date: Utc::now().date_naive().max(original.effective_date),
+                updates: original
+                    .updates
+                    .iter()
+                    .map(|update| BalanceUpdate {
+                        account_id: update.account_id,
+                        changed_by: update.changed_by.saturating_neg(),
+                        memo: update.memo.clone(),
+                    })
+                    .collect(),
+                exchange_rates: original.exchange_rates.clone(),
+                corrects: None,
+            },
+        },
+    );
+
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_sourcing::currency::ExchangeRate;
+    use crate::event_sourcing::journal::AccountType;
+
+    const BANK: Uuid = Uuid::from_u128(0xf5be0958_6c40_410a_a7de_8aef830c231d);
+    const CASH: Uuid = Uuid::from_u128(0x8bca9349_c9ba_4d10_96db_126ef1cc5954);
+
+    /// a dollar bank account, a euro cash account and 1 EUR = 1.095 USD
+    fn journal() -> JournalState {
+        let mut state = JournalState::default();
+        for (id, currency) in [(BANK, Currency::Usd), (CASH, Currency::Eur)] {
+            state.apply(
+                JournalEvent::CreatedAccount {
+                    id,
+                    account_name: currency.code().to_string(),
+                    currency,
+                    account_type: AccountType::Asset,
+                },
+                Utc::now(),
+            );
+        }
+        state.apply(
+            JournalEvent::RecordedExchangeRate {
+                rate: ExchangeRate {
+                    base: Currency::Eur,
+                    quote: Currency::Usd,
+                    rate: 109_500_000,
+                },
+            },
+            Utc::now(),
+        );
+        state
+    }
+
+    /// buys euros: `dollars` leave the bank and `euros` go into cash
+    fn exchange(dollars: &str, euros: &str) -> EntryForm {
+        EntryForm {
+            description: "Exchange".to_string(),
+            effective_date: Utc::now().date_naive(),
+            account_ids: vec![BANK.to_string(), CASH.to_string()],
+            balance_add_cents: vec![String::new(), euros.to_string()],
+            balance_remove_cents: vec![dollars.to_string(), String::new()],
+            memos: vec![String::new(), String::new()],
+        }
+    }
+
+    #[test]
+    fn a_rounding_difference_is_posted_to_the_fx_account() {
+        let state = journal();
+        let mut unit_of_work = UnitOfWork::new();
+
+        // 1.00 EUR is 1.095 USD, which rounds to 1.10, so a cent is left over
+        let pushed = push_entry(
+            exchange("1.09", "1.00"),
+            Uuid::nil(),
+            None,
+            &state,
+            &mut unit_of_work,
+        );
+        assert!(pushed.is_ok());
+
+        let events: Vec<&JournalEvent> = unit_of_work.journal_events().collect();
+        let [
+            JournalEvent::CreatedFxAccount {
+                id: fx_account,
+                currency: Currency::Usd,
+            },
+            JournalEvent::AddedEntry { transaction },
+        ] = events.as_slice()
+        else {
+            panic!("expected an fx account and the entry");
+        };
+        assert_eq!(
+            transaction.updates.last(),
+            Some(&BalanceUpdate {
+                account_id: *fx_account,
+                changed_by: -1,
+                memo: None,
+            })
+        );
+    }
+
+    #[test]
+    fn an_existing_fx_account_is_reused() {
+        let mut state = journal();
+        let fx_account = Uuid::new_v4();
+        state.apply(
+            JournalEvent::CreatedFxAccount {
+                id: fx_account,
+                currency: Currency::Usd,
+            },
+            Utc::now(),
+        );
+        let mut unit_of_work = UnitOfWork::new();
+
+        let pushed = push_entry(
+            exchange("1.11", "1.00"),
+            Uuid::nil(),
+            None,
+            &state,
+            &mut unit_of_work,
+        );
+        assert!(pushed.is_ok());
+
+        let events: Vec<&JournalEvent> = unit_of_work.journal_events().collect();
+        let [JournalEvent::AddedEntry { transaction }] = events.as_slice() else {
+            panic!("expected only the entry");
+        };
+        assert_eq!(
+            transaction.updates.last(),
+            Some(&BalanceUpdate {
+                account_id: fx_account,
+                changed_by: 1,
+                memo: None,
+            })
+        );
+    }
+
+    #[test]
+    fn a_difference_beyond_rounding_is_refused() {
+        let state = journal();
+        let mut unit_of_work = UnitOfWork::new();
+
+        // two cents off with a single converted line
+        let pushed = push_entry(
+            exchange("1.08", "1.00"),
+            Uuid::nil(),
+            None,
+            &state,
+            &mut unit_of_work,
+        );
+
+        let error = pushed.err().and_then(|e| KnownErrors::parse_error(&e));
+        assert!(matches!(
+            error,
+            Some(KnownErrors::BalanceMismatch {
+                currency: Currency::Usd,
+                ..
+            })
+        ));
+        assert_eq!(unit_of_work.journal_events().count(), 0);
+    }
+
+    #[test]
+    fn an_overflowing_fx_line_names_its_row() {
+        let mut state = journal();
+        let fx_account = Uuid::new_v4();
+        let wallet = Uuid::new_v4();
+        state.apply(
+            JournalEvent::CreatedFxAccount {
+                id: fx_account,
+                currency: Currency::Usd,
+            },
+            Utc::now(),
+        );
+        state.apply(
+            JournalEvent::CreatedAccount {
+                id: wallet,
+                account_name: "Wallet".to_string(),
+                currency: Currency::Eur,
+                account_type: AccountType::Asset,
+            },
+            Utc::now(),
+        );
+        let mut unit_of_work = UnitOfWork::new();
+
+        // 2.00 EUR is 2.19 USD against 2.17 USD entered, and the fx line can't take the
+        // two cents on top of the largest amount there is
+        let form = EntryForm {
+            description: "Exchange".to_string(),
+            effective_date: Utc::now().date_naive(),
+            account_ids: vec![
+                BANK.to_string(),
+                fx_account.to_string(),
+                CASH.to_string(),
+                wallet.to_string(),
+            ],
+            balance_add_cents: vec![
+                "92233720368547755.90".to_string(),
+                String::new(),
+                "1.00".to_string(),
+                "1.00".to_string(),
+            ],
+            balance_remove_cents: vec![
+                String::new(),
+                "92233720368547758.07".to_string(),
+                String::new(),
+                String::new(),
+            ],
+            memos: vec![String::new(); 4],
+        };
+
+        let pushed = push_entry(form, Uuid::nil(), None, &state, &mut unit_of_work);
+
+        let error = pushed.err().and_then(|e| KnownErrors::parse_error(&e));
+        assert!(matches!(
+            error,
+            Some(KnownErrors::AmountOverflow { row: 2 })
+        ));
+    }
+}
@@ -1,19 +1,23 @@
+use super::authorization;
+use super::entry::{self, EntryForm};
 use super::extensions;
+use super::money;
 use super::return_types::*;
 use crate::event_sourcing;
 use crate::event_sourcing::auth;
 use crate::event_sourcing::auth::AuthEvent;
+use crate::event_sourcing::currency::{Currency, ExchangeRate};
 use crate::event_sourcing::journal;
 use crate::event_sourcing::journal::JournalEventType;
 use crate::event_sourcing::username;
-use chrono::Utc;
-use event_sourcing::journal::{
-    BalanceUpdate, JournalEvent, JournalState, Permissions, Transaction,
-};
+use chrono::NaiveDate;
+use event_sourcing::journal::{AccountType, JournalEvent, JournalState, Permissions};
+use event_sourcing::unit_of_work::UnitOfWork;
 use event_sourcing::user;
 use event_sourcing::user::{UserEvent, UserState};
 use leptos::prelude::*;
-use postcard::from_bytes;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 #[server]
@@ -61,15 +65,18 @@ pub async fn create_user(
 
     if username::get_id(&username, &pool).await?.is_none() {
         let uuid = Uuid::new_v4();
-        UserEvent::Created {
-            hashed_password: bcrypt::hash(password, bcrypt::DEFAULT_COST)?,
-        }
-        .push_db(&uuid, &pool)
-        .await?;
 
-        username::update(&uuid, &username, &pool).await?;
-
-        AuthEvent::Login.push_db(&uuid, &session_id, &pool).await?;
+        let mut work = UnitOfWork::new();
+        work.push_user(
+            uuid,
+            0,
+            UserEvent::Created {
+                hashed_password: bcrypt::hash(password, bcrypt::DEFAULT_COST)?,
+            },
+        )
+        .push_username(uuid, username)
+        .push_auth(uuid, session_id, AuthEvent::Login);
+        work.commit(&pool).await?;
     } else {
         return Err(ServerFnError::ServerError(
             KnownErrors::UserExists { username }.to_string()?,
@@ -136,62 +143,180 @@ pub async fn create_journal(journal_name: String) -> Result<(), ServerFnError> {
 
     let journal_id = Uuid::new_v4();
 
-    JournalEvent::Created {
-        name: journal_name,
-        owner: user_id,
+    let user_state =
+        UserState::build(&user_id, vec![user::UserEventType::CreatedJournal], &pool).await?;
+
+    let mut work = UnitOfWork::new();
+    work.push_journal(
+        journal_id,
+        0,
+        JournalEvent::Created {
+            name: journal_name,
+            owner: user_id,
+        },
+    )
+    .push_user(
+        user_id,
+        user_state.sequence,
+        UserEvent::CreatedJournal { id: journal_id },
+    );
+    work.commit(&pool).await?;
+
+    Ok(())
+}
+
+#[server]
+pub async fn select_journal(journal_id: String) -> Result<(), ServerFnError> {
+    let journal_id = Uuid::try_from(journal_id)?;
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    let access = authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    UserEvent::SelectedJournal { id: journal_id }
+        .push_db(&user_id, access.user_sequence, &pool)
+        .await?;
+
+    Ok(())
+}
+
+#[server]
+pub async fn rename_journal(journal_id: Uuid, name: String) -> Result<(), ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    if name.trim().is_empty() {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidInput.to_string()?,
+        ));
+    }
+
+    let access =
+        authorization::authorize(&user_id, &journal_id, Permissions::DELETE, &pool).await?;
+
+    JournalEvent::Renamed {
+        name: name.trim().to_string(),
     }
-    .push_db(&journal_id, &pool)
+    .push_db(&journal_id, access.journal_sequence, &pool)
     .await?;
 
-    UserEvent::CreatedJournal { id: journal_id }
-        .push_db(&user_id, &pool)
+    Ok(())
+}
+
+/// hides the journal from everyone. it can be restored for `RESTORE_GRACE_DAYS`
+#[server]
+pub async fn delete_journal(journal_id: Uuid) -> Result<(), ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    let access =
+        authorization::authorize(&user_id, &journal_id, Permissions::DELETE, &pool).await?;
+
+    JournalEvent::Deleted
+        .push_db(&journal_id, access.journal_sequence, &pool)
         .await?;
 
+    leptos_axum::redirect("/journal");
+
     Ok(())
 }
 
 #[server]
-pub async fn select_journal(journal_id: String) -> Result<(), ServerFnError> {
-    use user::UserEventType::*;
-
-    let journal_id = Uuid::try_from(journal_id)?;
+pub async fn restore_journal(journal_id: Uuid) -> Result<(), ServerFnError> {
     let session_id = extensions::get_session_id().await?;
     let pool = extensions::get_pool().await?;
+
     let user_id = auth::get_user_id(&session_id, &pool).await?;
 
-    let user_state = UserState::build(
-        &user_id,
-        vec![
-            CreatedJournal,
-            InvitedToJournal,
-            AcceptedJournalInvite,
-            DeclinedJournalInvite,
-            RemovedFromJournal,
-        ],
+    // `authorize` refuses deleted journals, so check the permission on its own
+    let user_state = authorization::access_state(&user_id, &pool).await?;
+    if let Err(e) = authorization::check(&user_id, &user_state, &journal_id, Permissions::DELETE) {
+        return Err(ServerFnError::ServerError(e.to_string()?));
+    }
+
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![JournalEventType::Deleted, JournalEventType::Restored],
         &pool,
     )
     .await?;
 
-    if !user_state.owned_journals.contains(&journal_id) {
-        let journal = user_state.accepted_journal_invites.get(&journal_id);
+    if !journal_state.deleted {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidJournal.to_string()?,
+        ));
+    }
 
-        if !journal.is_some_and(|j| j.tenant_permissions.contains(Permissions::READ)) {
-            return Err(ServerFnError::ServerError(
-                KnownErrors::PermissionError {
-                    required_permissions: Permissions::READ,
-                }
-                .to_string()?,
-            ));
-        }
+    let deleted_at = journal::deleted_at(&journal_id, &pool).await?;
+    if deleted_at.is_none_or(|deleted_at| {
+        deleted_at + chrono::Duration::days(journal::RESTORE_GRACE_DAYS) < chrono::Utc::now()
+    }) {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::RestorePeriodExpired.to_string()?,
+        ));
     }
 
-    UserEvent::SelectedJournal { id: journal_id }
-        .push_db(&user_id, &pool)
+    JournalEvent::Restored
+        .push_db(&journal_id, journal_state.sequence, &pool)
         .await?;
 
     Ok(())
 }
 
+/// journals the user could restore, newest deletion first
+#[server]
+pub async fn get_deleted_journals() -> Result<Vec<DeletedJournal>, ServerFnError> {
+    use journal::JournalEventType::{Created, Deleted, Renamed, Restored};
+
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    let user_state = authorization::access_state(&user_id, &pool).await?;
+
+    let journal_ids = user_state.owned_journals.iter().chain(
+        user_state
+            .accepted_journal_invites
+            .iter()
+            .filter(|(_, tenant_info)| tenant_info.tenant_permissions.contains(Permissions::DELETE))
+            .map(|(id, _)| id),
+    );
+
+    let mut deleted = Vec::new();
+
+    for journal_id in journal_ids {
+        let journal_state =
+            JournalState::build(journal_id, vec![Created, Renamed, Deleted, Restored], &pool)
+                .await?;
+
+        if !journal_state.deleted {
+            continue;
+        }
+
+        if let Some(deleted_at) = journal::deleted_at(journal_id, &pool).await? {
+            let restorable_until = deleted_at + chrono::Duration::days(journal::RESTORE_GRACE_DAYS);
+            if restorable_until >= chrono::Utc::now() {
+                deleted.push(DeletedJournal {
+                    id: *journal_id,
+                    name: journal_state.name,
+                    deleted_at,
+                    restorable_until,
+                });
+            }
+        }
+    }
+
+    deleted.sort_by_key(|journal| std::cmp::Reverse(journal.deleted_at));
+
+    Ok(deleted)
+}
+
 #[server]
 pub async fn invite_to_journal(
     journal_id: String,
@@ -209,20 +334,13 @@ pub async fn invite_to_journal(
 
     let own_id = auth::get_user_id(&session_id, &pool).await?;
 
-    if let Some(invitee_id) = username::get_id(&invitee_username, &pool).await? {
-        let inviting_user_state = UserState::build(
-            &own_id,
-            vec![
-                CreatedJournal,
-                InvitedToJournal,
-                AcceptedJournalInvite,
-                DeclinedJournalInvite,
-                RemovedFromJournal,
-            ],
-            &pool,
-        )
-        .await?;
+    let access = authorization::authorize(&own_id, &journal_id, Permissions::INVITE, &pool).await?;
+
+    if let Err(e) = access.require(permissions) {
+        return Err(ServerFnError::ServerError(e.to_string()?));
+    }
 
+    if let Some(invitee_id) = username::get_id(&invitee_username, &pool).await? {
         let invitee_state = UserState::build(
             &invitee_id,
             vec![
@@ -249,38 +367,15 @@ pub async fn invite_to_journal(
             ));
         }
 
-        if inviting_user_state.owned_journals.contains(&journal_id) {
-            UserEvent::InvitedToJournal {
-                id: journal_id,
-                permissions,
-                inviting_user: own_id,
-                owner: own_id,
-            }
-            .push_db(&invitee_id, &pool)
-            .await?;
-        } else if let Some(own_tenant_info) = inviting_user_state
-            .accepted_journal_invites
-            .get(&journal_id)
-        {
-            for permission in permissions {
-                if !own_tenant_info.tenant_permissions.contains(permission) {
-                    return Err(ServerFnError::ServerError(
-                        KnownErrors::PermissionError {
-                            required_permissions: permission,
-                        }
-                        .to_string()?,
-                    ));
-                }
-            }
-            UserEvent::InvitedToJournal {
-                id: journal_id,
-                permissions,
-                inviting_user: own_id,
-                owner: own_tenant_info.journal_owner,
-            }
-            .push_db(&invitee_id, &pool)
-            .await?;
+        UserEvent::InvitedToJournal {
+            id: journal_id,
+            permissions,
+            inviting_user: own_id,
+            owner: access.owner(),
         }
+        .push_db(&invitee_id, invitee_state.sequence, &pool)
+        .await?;
+
         Ok(())
     } else {
         Err(ServerFnError::ServerError(
@@ -289,9 +384,78 @@ pub async fn invite_to_journal(
     }
 }
 
+/// removes a tenant from the journal. tenants can only remove people who hold no
+/// permission they don't hold themselves
+#[server]
+pub async fn remove_from_journal(journal_id: Uuid, tenant_id: Uuid) -> Result<(), ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    let access =
+        authorization::authorize(&user_id, &journal_id, Permissions::INVITE, &pool).await?;
+
+    let tenant_state = authorization::access_state(&tenant_id, &pool).await?;
+    let Some(tenant_info) = tenant_state.accepted_journal_invites.get(&journal_id) else {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::NotATenant.to_string()?,
+        ));
+    };
+
+    if let Err(e) = access.require(tenant_info.tenant_permissions) {
+        return Err(ServerFnError::ServerError(e.to_string()?));
+    }
+
+    UserEvent::RemovedFromJournal { id: journal_id }
+        .push_db(&tenant_id, tenant_state.sequence, &pool)
+        .await?;
+
+    Ok(())
+}
+
+/// replaces a tenant's permissions. like with invites, nobody can grant more than they
+/// hold, or change the permissions of someone who holds more than they do
+#[server]
+pub async fn update_tenant_permissions(
+    journal_id: Uuid,
+    tenant_id: Uuid,
+    permissions: String,
+) -> Result<(), ServerFnError> {
+    let permissions: Permissions = serde_json::from_str(&permissions)?;
+
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    let access =
+        authorization::authorize(&user_id, &journal_id, Permissions::INVITE, &pool).await?;
+
+    let tenant_state = authorization::access_state(&tenant_id, &pool).await?;
+    let Some(tenant_info) = tenant_state.accepted_journal_invites.get(&journal_id) else {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::NotATenant.to_string()?,
+        ));
+    };
+
+    if let Err(e) = access.require(permissions | tenant_info.tenant_permissions) {
+        return Err(ServerFnError::ServerError(e.to_string()?));
+    }
+
+    UserEvent::TenantPermissionsUpdated {
+        id: journal_id,
+        permissions,
+    }
+    .push_db(&tenant_id, tenant_state.sequence, &pool)
+    .await?;
+
+    Ok(())
+}
+
 #[server]
 pub async fn get_journal_invites() -> Result<Vec<JournalInvite>, ServerFnError> {
-    use journal::JournalEventType::{Created, *};
+    use journal::JournalEventType::{Created, Deleted, Renamed, Restored};
     use user::UserEventType::*;
 
     let mut invites = Vec::new();
@@ -314,7 +478,12 @@ pub async fn get_journal_invites() -> Result<Vec<JournalInvite>, ServerFnError>
     .await?;
 
     for (id, tenant_info) in user_state.pending_journal_invites {
-        let journal_state = JournalState::build(&id, vec![Created, Renamed], &pool).await?;
+        let journal_state =
+            JournalState::build(&id, vec![Created, Renamed, Deleted, Restored], &pool).await?;
+
+        if journal_state.deleted {
+            continue;
+        }
 
         invites.push(JournalInvite {
             id,
@@ -356,12 +525,25 @@ pub async fn respond_to_journal_invite(
 
     if user_state.pending_journal_invites.contains_key(&journal_id) {
         if accepted {
+            let journal_state = JournalState::build(
+                &journal_id,
+                vec![JournalEventType::Deleted, JournalEventType::Restored],
+                &pool,
+            )
+            .await?;
+
+            if journal_state.deleted {
+                return Err(ServerFnError::ServerError(
+                    KnownErrors::InvalidJournal.to_string()?,
+                ));
+            }
+
             UserEvent::AcceptedJournalInvite { id: journal_id }
-                .push_db(&user_id, &pool)
+                .push_db(&user_id, user_state.sequence, &pool)
                 .await?;
         } else {
             UserEvent::DeclinedJournalInvite { id: journal_id }
-                .push_db(&user_id, &pool)
+                .push_db(&user_id, user_state.sequence, &pool)
                 .await?;
         }
     } else {
@@ -375,7 +557,7 @@ pub async fn respond_to_journal_invite(
 
 #[server]
 pub async fn get_associated_journals() -> Result<Journals, ServerFnError> {
-    use journal::JournalEventType::{Created, Deleted};
+    use journal::JournalEventType::{Created, Deleted, Renamed, Restored};
     use user::UserEventType::*;
     let mut journals = Vec::new();
 
@@ -394,6 +576,7 @@ pub async fn get_associated_journals() -> Result<Journals, ServerFnError> {
             AcceptedJournalInvite,
             DeclinedJournalInvite,
             RemovedFromJournal,
+            TenantPermissionsUpdated,
             SelectedJournal,
         ],
         &pool,
@@ -401,7 +584,12 @@ pub async fn get_associated_journals() -> Result<Journals, ServerFnError> {
     .await?;
 
     for journal_id in user.owned_journals {
-        let journal_state = JournalState::build(&journal_id, vec![Created, Deleted], &pool).await?;
+        let journal_state = JournalState::build(
+            &journal_id,
+            vec![Created, Renamed, Deleted, Restored],
+            &pool,
+        )
+        .await?;
         if !journal_state.deleted {
             journals.push(AssociatedJournal::Owned {
                 id: journal_id,
@@ -420,8 +608,12 @@ pub async fn get_associated_journals() -> Result<Journals, ServerFnError> {
     }
 
     for shared_journal in user.accepted_journal_invites {
-        let journal_state =
-            JournalState::build(&shared_journal.0, vec![Created, Deleted], &pool).await?;
+        let journal_state = JournalState::build(
+            &shared_journal.0,
+            vec![Created, Renamed, Deleted, Restored],
+            &pool,
+        )
+        .await?;
         if !journal_state.deleted {
             journals.push(AssociatedJournal::Shared {
                 id: shared_journal.0,
@@ -448,10 +640,14 @@ pub async fn get_associated_journals() -> Result<Journals, ServerFnError> {
 }
 
 #[server]
-pub async fn get_journal_owner(journal_id: String) -> Result<Option<String>, ServerFnError> {
-    let journal_id = Uuid::parse_str(&journal_id)?;
+pub async fn get_journal_owner(journal_id: Uuid) -> Result<Option<String>, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
     let pool = extensions::get_pool().await?;
 
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
     let journal_state =
         JournalState::build(&journal_id, vec![JournalEventType::Created], &pool).await?;
 
@@ -459,70 +655,80 @@ pub async fn get_journal_owner(journal_id: String) -> Result<Option<String>, Ser
 }
 
 #[server]
-pub async fn get_accounts() -> Result<Vec<Account>, ServerFnError> {
-    use journal::JournalEventType::{Created, *};
-    use user::UserEventType::*;
-
-    let mut accounts = Vec::new();
-
+pub async fn get_journal_members(journal_id: Uuid) -> Result<Vec<JournalMember>, ServerFnError> {
     let session_id = extensions::get_session_id().await?;
     let pool = extensions::get_pool().await?;
 
     let user_id = auth::get_user_id(&session_id, &pool).await?;
 
-    let user_state = UserState::build(
-        &user_id,
-        vec![
-            CreatedJournal,
-            InvitedToJournal,
-            AcceptedJournalInvite,
-            DeclinedJournalInvite,
-            RemovedFromJournal,
-            SelectedJournal,
-        ],
-        &pool,
-    )
-    .await?;
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    let journal_state =
+        JournalState::build(&journal_id, vec![JournalEventType::Created], &pool).await?;
 
-    let journal_id = user_state.selected_journal;
+    let mut members = vec![JournalMember {
+        id: journal_state.owner,
+        username: username::get_username(&journal_state.owner, &pool)
+            .await?
+            .unwrap_or("unknown user".to_string()),
+        tenant_info: None,
+    }];
 
-    if journal_id.is_nil() {
-        return Err(ServerFnError::ServerError(
-            KnownErrors::InvalidJournal.to_string()?,
-        ));
+    for (id, tenant_info) in user::get_journal_tenants(&journal_id, &pool).await? {
+        members.push(JournalMember {
+            id,
+            username: username::get_username(&id, &pool)
+                .await?
+                .unwrap_or("unknown user".to_string()),
+            tenant_info: Some(tenant_info),
+        });
     }
 
-    if !user_state.owned_journals.contains(&journal_id) {
-        let journal_perms = user_state.accepted_journal_invites.get(&journal_id);
+    // the owner stays first
+    members[1..].sort_by(|a, b| a.username.cmp(&b.username));
 
-        if !journal_perms.is_some_and(|j| j.tenant_permissions.contains(Permissions::READ)) {
-            return Err(ServerFnError::ServerError(
-                KnownErrors::PermissionError {
-                    required_permissions: Permissions::READ,
-                }
-                .to_string()?,
-            ));
-        }
-    }
+    Ok(members)
+}
 
-    let journal_state = JournalState::build(
-        &journal_id,
-        vec![Created, CreatedAccount, DeletedAccount, AddedEntry],
-        &pool,
-    )
-    .await?;
+/// the journal's top level accounts with their sub-accounts nested inside, sorted by name
+#[server]
+pub async fn get_accounts(journal_id: Uuid) -> Result<Vec<Account>, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
 
-    for (id, (name, balance)) in journal_state.accounts {
-        accounts.push(Account { id, name, balance });
-    }
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
 
-    Ok(accounts)
+    read_accounts(&journal_id, journal::AsOf::Latest, &pool).await
 }
 
+/// the accounts as they stood just before `as_of`, including ones that were deleted since.
+/// access is checked against the user's permissions now, not at `as_of`
 #[server]
-pub async fn add_account(journal_id: Uuid, account_name: String) -> Result<(), ServerFnError> {
-    use user::UserEventType::*;
+pub async fn get_accounts_as_of(
+    journal_id: Uuid,
+    as_of: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<Account>, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
 
+    read_accounts(&journal_id, journal::AsOf::Time(as_of), &pool).await
+}
+
+#[server]
+pub async fn add_account(
+    journal_id: Uuid,
+    account_name: String,
+    currency: Currency,
+    account_type: AccountType,
+    // empty for a top level account
+    #[server(default)] parent_id: String,
+) -> Result<(), ServerFnError> {
     let session_id = extensions::get_session_id().await?;
     let pool = extensions::get_pool().await?;
 
@@ -534,230 +740,1313 @@ pub async fn add_account(journal_id: Uuid, account_name: String) -> Result<(), S
         ));
     }
 
-    let user_state = user::UserState::build(
-        &user_id,
+    authorization::authorize(&user_id, &journal_id, Permissions::ADDACCOUNT, &pool).await?;
+
+    let mut journal_state = JournalState::build(
+        &journal_id,
         vec![
-            CreatedJournal,
-            InvitedToJournal,
-            AcceptedJournalInvite,
-            DeclinedJournalInvite,
-            RemovedFromJournal,
+            JournalEventType::CreatedAccount,
+            JournalEventType::DeletedAccount,
+            JournalEventType::CreatedFxAccount,
+            JournalEventType::ArchivedAccount,
+            JournalEventType::MovedAccount,
         ],
         &pool,
     )
     .await?;
 
-    if user_state.owned_journals.contains(&journal_id)
-        || user_state
-            .accepted_journal_invites
-            .get(&journal_id)
-            .is_some_and(|tenant_info| {
-                tenant_info
-                    .tenant_permissions
-                    .contains(Permissions::ADDACCOUNT)
-            })
-    {
-        JournalEvent::CreatedAccount { account_name }
-            .push_db(&journal_id, &pool)
-            .await?;
-    } else {
-        return Err(ServerFnError::ServerError(
-            KnownErrors::PermissionError {
-                required_permissions: Permissions::ADDACCOUNT,
-            }
-            .to_string()?,
-        ));
-    }
+    let id = Uuid::new_v4();
+    let created = JournalEvent::CreatedAccount {
+        id,
+        account_name,
+        currency,
+        account_type,
+    };
 
-    Ok(())
-}
+    let mut unit_of_work = UnitOfWork::new();
+    unit_of_work.push_journal(journal_id, journal_state.sequence, created.clone());
 
-#[server]
-pub async fn transact(
-    journal_id: String,
-    account_ids: Vec<Uuid>,
-    balance_add_cents: Vec<String>,
-    balance_remove_cents: Vec<String>,
-) -> Result<(), ServerFnError> {
-    use user::UserEventType::*;
+    if !parent_id.trim().is_empty() {
+        let parent = Uuid::try_parse(parent_id.trim())?;
 
-    let journal_id = Uuid::try_parse(&journal_id)?;
+        // the new account has to be in the state to compare it with its parent
+        journal_state.apply(created, chrono::Utc::now());
+        if !journal_state.can_parent(&id, &parent) {
+            return Err(ServerFnError::ServerError(
+                KnownErrors::InvalidParent.to_string()?,
+            ));
+        }
+
+        unit_of_work.push_journal(
+            journal_id,
+            journal_state.sequence,
+            JournalEvent::MovedAccount {
+                account_id: id,
+                parent: Some(parent),
+            },
+        );
+    }
+
+    unit_of_work.commit(&pool).await?;
 
+    Ok(())
+}
+
+/// puts an account under another one of the same type and currency, or back at the
+/// top level when `parent_id` is empty
+#[server]
+pub async fn move_account(
+    journal_id: Uuid,
+    account_id: Uuid,
+    parent_id: String,
+) -> Result<(), ServerFnError> {
     let session_id = extensions::get_session_id().await?;
     let pool = extensions::get_pool().await?;
 
     let user_id = auth::get_user_id(&session_id, &pool).await?;
 
-    let user_state = UserState::build(
-        &user_id,
+    authorization::authorize(&user_id, &journal_id, Permissions::ADDACCOUNT, &pool).await?;
+
+    let journal_state = JournalState::build(
+        &journal_id,
         vec![
-            CreatedJournal,
-            InvitedToJournal,
-            AcceptedJournalInvite,
-            DeclinedJournalInvite,
-            RemovedFromJournal,
+            JournalEventType::CreatedAccount,
+            JournalEventType::DeletedAccount,
+            JournalEventType::CreatedFxAccount,
+            JournalEventType::ArchivedAccount,
+            JournalEventType::MovedAccount,
         ],
         &pool,
     )
     .await?;
 
-    if !user_state.owned_journals.contains(&journal_id) {
-        if let Some(tenant_info) = user_state.accepted_journal_invites.get(&journal_id) {
-            if !tenant_info
-                .tenant_permissions
-                .contains(Permissions::APPENDTRANSACTION)
-            {
-                return Err(ServerFnError::ServerError(
-                    KnownErrors::PermissionError {
-                        required_permissions: Permissions::APPENDTRANSACTION,
-                    }
-                    .to_string()?,
-                ));
-            }
-        } else {
+    if !journal_state.accounts.contains_key(&account_id) {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::AccountNotFound.to_string()?,
+        ));
+    }
+
+    let parent = if parent_id.trim().is_empty() {
+        None
+    } else {
+        let parent = Uuid::try_parse(parent_id.trim())?;
+        if !journal_state.can_parent(&account_id, &parent) {
             return Err(ServerFnError::ServerError(
-                KnownErrors::PermissionError {
-                    required_permissions: Permissions::APPENDTRANSACTION,
-                }
-                .to_string()?,
+                KnownErrors::InvalidParent.to_string()?,
             ));
         }
+        Some(parent)
+    };
+
+    JournalEvent::MovedAccount { account_id, parent }
+        .push_db(&journal_id, journal_state.sequence, &pool)
+        .await?;
+
+    Ok(())
+}
+
+#[server]
+pub async fn rename_account(
+    journal_id: Uuid,
+    account_id: Uuid,
+    name: String,
+) -> Result<(), ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    if name.trim().is_empty() {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidInput.to_string()?,
+        ));
+    }
+
+    authorization::authorize(&user_id, &journal_id, Permissions::ADDACCOUNT, &pool).await?;
+
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![
+            JournalEventType::CreatedAccount,
+            JournalEventType::DeletedAccount,
+            JournalEventType::CreatedFxAccount,
+        ],
+        &pool,
+    )
+    .await?;
+
+    if !journal_state.accounts.contains_key(&account_id) {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::AccountNotFound.to_string()?,
+        ));
+    }
+
+    JournalEvent::RenamedAccount {
+        account_id,
+        name: name.trim().to_string(),
     }
+    .push_db(&journal_id, journal_state.sequence, &pool)
+    .await?;
 
-    let mut updates: Vec<BalanceUpdate> = Vec::new();
-    let mut total_balance_change: i64 = 0;
+    Ok(())
+}
 
-    for i in 0..balance_add_cents.len() {
-        let add_amt = (balance_add_cents[i].parse::<f64>().unwrap_or(0.0) * 100.0) as i64;
+/// hides an account that's no longer used. fx accounts can't be archived since
+/// entries across currencies post to them automatically
+#[server]
+pub async fn archive_account(journal_id: Uuid, account_id: Uuid) -> Result<(), ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
 
-        let remove_amt = (balance_remove_cents[i].parse::<f64>().unwrap_or(0.0) * 100.0) as i64;
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
 
-        let account_sum = add_amt - remove_amt;
+    authorization::authorize(&user_id, &journal_id, Permissions::ADDACCOUNT, &pool).await?;
 
-        if account_sum != 0 {
-            total_balance_change += account_sum;
-            updates.push(BalanceUpdate {
-                account_id: account_ids[i],
-                changed_by: account_sum,
-            });
-        }
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![
+            JournalEventType::CreatedAccount,
+            JournalEventType::DeletedAccount,
+            JournalEventType::CreatedFxAccount,
+            JournalEventType::ArchivedAccount,
+            JournalEventType::MovedAccount,
+        ],
+        &pool,
+    )
+    .await?;
+
+    let Some(account) = journal_state.accounts.get(&account_id) else {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::AccountNotFound.to_string()?,
+        ));
+    };
+
+    if account.archived
+        || journal_state
+            .fx_accounts
+            .values()
+            .any(|id| *id == account_id)
+    {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidInput.to_string()?,
+        ));
     }
 
-    if total_balance_change != 0 {
+    // archiving a parent would hide sub-accounts that are still in use
+    if journal_state
+        .accounts
+        .values()
+        .any(|child| child.parent == Some(account_id) && !child.archived)
+    {
         return Err(ServerFnError::ServerError(
-            KnownErrors::BalanceMismatch {
-                attempted_transaction: updates,
-            }
-            .to_string()?,
+            KnownErrors::AccountHasChildren.to_string()?,
+        ));
+    }
+
+    JournalEvent::ArchivedAccount { account_id }
+        .push_db(&journal_id, journal_state.sequence, &pool)
+        .await?;
+
+    Ok(())
+}
+
+/// removes an account that was created by mistake. accounts with a balance or any
+/// entries have to be archived instead, so the history stays complete
+#[server]
+pub async fn delete_account(journal_id: Uuid, account_id: Uuid) -> Result<(), ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::ADDACCOUNT, &pool).await?;
+
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![
+            JournalEventType::CreatedAccount,
+            JournalEventType::DeletedAccount,
+            JournalEventType::CreatedFxAccount,
+            JournalEventType::AddedEntry,
+            JournalEventType::ReversedEntry,
+            JournalEventType::MovedAccount,
+        ],
+        &pool,
+    )
+    .await?;
+
+    let Some(account) = journal_state.accounts.get(&account_id) else {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::AccountNotFound.to_string()?,
+        ));
+    };
+
+    if journal_state
+        .accounts
+        .values()
+        .any(|child| child.parent == Some(account_id))
+    {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::AccountHasChildren.to_string()?,
         ));
     }
 
-    if updates.is_empty() {
+    let referenced = journal_state.transactions.iter().any(|transaction| {
+        transaction
+            .updates
+            .iter()
+            .any(|update| update.account_id == account_id)
+    });
+
+    if account.balance != 0
+        || referenced
+        || journal_state
+            .fx_accounts
+            .values()
+            .any(|id| *id == account_id)
+    {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::AccountInUse.to_string()?,
+        ));
+    }
+
+    JournalEvent::DeletedAccount { account_id }
+        .push_db(&journal_id, journal_state.sequence, &pool)
+        .await?;
+
+    Ok(())
+}
+
+#[server]
+pub async fn record_exchange_rate(
+    journal_id: Uuid,
+    base: Currency,
+    quote: Currency,
+    rate: String,
+) -> Result<(), ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    if base == quote {
         return Err(ServerFnError::ServerError(
             KnownErrors::InvalidInput.to_string()?,
         ));
     }
 
-    JournalEvent::AddedEntry {
-        transaction: Transaction {
-            author: user_id,
-            updates,
+    let rate = money::parse_rate(&rate)?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::APPENDTRANSACTION, &pool).await?;
+
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![JournalEventType::RecordedExchangeRate],
+        &pool,
+    )
+    .await?;
+
+    JournalEvent::RecordedExchangeRate {
+        rate: ExchangeRate { base, quote, rate },
+    }
+    .push_db(&journal_id, journal_state.sequence, &pool)
+    .await?;
+
+    Ok(())
+}
+
+#[server]
+pub async fn get_exchange_rates(journal_id: Uuid) -> Result<Vec<ExchangeRate>, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![JournalEventType::RecordedExchangeRate],
+        &pool,
+    )
+    .await?;
+
+    Ok(journal_state.exchange_rates.into_values().collect())
+}
+
+#[server]
+pub async fn transact(
+    journal_id: String,
+    description: String,
+    effective_date: NaiveDate,
+    account_ids: Vec<String>,
+    balance_add_cents: Vec<String>,
+    balance_remove_cents: Vec<String>,
+    // one per row, forms without memo fields can leave them out
+    #[server(default)] memos: Vec<String>,
+) -> Result<Uuid, ServerFnError> {
+    let journal_id = Uuid::try_parse(&journal_id)?;
+
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::APPENDTRANSACTION, &pool).await?;
+
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![
+            JournalEventType::CreatedAccount,
+            JournalEventType::DeletedAccount,
+            JournalEventType::RecordedExchangeRate,
+            JournalEventType::CreatedFxAccount,
+            JournalEventType::ArchivedAccount,
+        ],
+        &pool,
+    )
+    .await?;
+
+    let mut unit_of_work = UnitOfWork::new();
+
+    let transaction_id = entry::push_entry(
+        EntryForm {
+            description,
+            effective_date,
+            account_ids,
+            balance_add_cents,
+            balance_remove_cents,
+            memos,
         },
+        user_id,
+        None,
+        &journal_state,
+        &mut unit_of_work,
+    )?;
+
+    unit_of_work.commit(&pool).await?;
+
+    Ok(transaction_id)
+}
+
+#[server]
+pub async fn reverse_transaction(
+    journal_id: Uuid,
+    transaction_id: Uuid,
+    reason: String,
+) -> Result<(), ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    if reason.trim().is_empty() {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidInput.to_string()?,
+        ));
     }
-    .push_db(&journal_id, &pool)
+
+    authorization::authorize(&user_id, &journal_id, Permissions::APPENDTRANSACTION, &pool).await?;
+
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![
+            JournalEventType::AddedEntry,
+            JournalEventType::ReversedEntry,
+        ],
+        &pool,
+    )
     .await?;
 
+    let original = reversible_entry(&journal_state, &transaction_id)?;
+
+    let mut unit_of_work = UnitOfWork::new();
+    _ = entry::push_reversal(
+        original,
+        reason.trim().to_string(),
+        user_id,
+        &journal_state,
+        &mut unit_of_work,
+    );
+    unit_of_work.commit(&pool).await?;
+
     Ok(())
 }
 
+/// reverses an entry and posts its replacement in one go, returning the replacement's id
+#[server]
+#[allow(clippy::too_many_arguments)]
+pub async fn correct_transaction(
+    journal_id: Uuid,
+    transaction_id: Uuid,
+    reason: String,
+    description: String,
+    effective_date: NaiveDate,
+    account_ids: Vec<String>,
+    balance_add_cents: Vec<String>,
+    balance_remove_cents: Vec<String>,
+    #[server(default)] memos: Vec<String>,
+) -> Result<Uuid, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    if reason.trim().is_empty() {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidInput.to_string()?,
+        ));
+    }
+
+    authorization::authorize(&user_id, &journal_id, Permissions::APPENDTRANSACTION, &pool).await?;
+
+    let journal_state = JournalState::build(
+        &journal_id,
+        vec![
+            JournalEventType::CreatedAccount,
+            JournalEventType::DeletedAccount,
+            JournalEventType::RecordedExchangeRate,
+            JournalEventType::CreatedFxAccount,
+            JournalEventType::ArchivedAccount,
+            JournalEventType::AddedEntry,
+            JournalEventType::ReversedEntry,
+        ],
+        &pool,
+    )
+    .await?;
+
+    let original = reversible_entry(&journal_state, &transaction_id)?;
+
+    let mut unit_of_work = UnitOfWork::new();
+
+    // the reversal has to come first so replaying the replacement links it up
+    _ = entry::push_reversal(
+        original,
+        reason.trim().to_string(),
+        user_id,
+        &journal_state,
+        &mut unit_of_work,
+    );
+
+    let replacement_id = entry::push_entry(
+        EntryForm {
+            description,
+            effective_date,
+            account_ids,
+            balance_add_cents,
+            balance_remove_cents,
+            memos,
+        },
+        user_id,
+        Some(transaction_id),
+        &journal_state,
+        &mut unit_of_work,
+    )?;
+
+    unit_of_work.commit(&pool).await?;
+
+    Ok(replacement_id)
+}
+
+#[server]
 pub async fn get_transactions(
-    journals: Result<Journals, ServerFnError>,
+    journal_id: Uuid,
 ) -> Result<Vec<TransactionWithTimeStamp>, ServerFnError> {
-    use user::UserEventType::*;
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
 
-    let journal_id = match journals?.selected {
-        Some(s) => s.get_id(),
-        None => {
-            return Err(ServerFnError::ServerError(
-                KnownErrors::InvalidJournal.to_string()?,
-            ));
-        }
-    };
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
 
-    let mut bundled_transactions = Vec::new();
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
 
+    read_transactions(&journal_id, journal::AsOf::Latest, &pool).await
+}
+
+/// the entries recorded before `as_of`, with only the reversals that had been made by then
+#[server]
+pub async fn get_transactions_as_of(
+    journal_id: Uuid,
+    as_of: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<TransactionWithTimeStamp>, ServerFnError> {
     let session_id = extensions::get_session_id().await?;
     let pool = extensions::get_pool().await?;
 
     let user_id = auth::get_user_id(&session_id, &pool).await?;
 
-    let user_state = UserState::build(
-        &user_id,
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    read_transactions(&journal_id, journal::AsOf::Time(as_of), &pool).await
+}
+
+#[server]
+pub async fn get_transaction(
+    journal_id: Uuid,
+    transaction_id: Uuid,
+) -> Result<TransactionDetail, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    // deleted accounts have to stay in the state so old entries can still name them
+    let journal_state = JournalState::build(
+        &journal_id,
         vec![
-            CreatedJournal,
-            InvitedToJournal,
-            AcceptedJournalInvite,
-            DeclinedJournalInvite,
-            RemovedFromJournal,
+            JournalEventType::CreatedAccount,
+            JournalEventType::CreatedFxAccount,
+            JournalEventType::RenamedAccount,
+            JournalEventType::AddedEntry,
+            JournalEventType::ReversedEntry,
         ],
         &pool,
     )
     .await?;
 
-    if !user_state.owned_journals.contains(&journal_id) {
-        let shared_journal = user_state.accepted_journal_invites.get(&journal_id);
-        if !shared_journal.is_some_and(|j| j.tenant_permissions.contains(Permissions::READ)) {
-            return Err(ServerFnError::ServerError(
-                KnownErrors::PermissionError {
-                    required_permissions: Permissions::READ,
-                }
-                .to_string()?,
-            ));
-        }
+    let Some(transaction) = journal_state
+        .transactions
+        .iter()
+        .find(|t| t.id == transaction_id)
+    else {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::TransactionNotFound.to_string()?,
+        ));
+    };
+
+    let author = username::get_username(&transaction.author, &pool)
+        .await?
+        .unwrap_or("unknown user".to_string());
+
+    let lines = transaction
+        .updates
+        .iter()
+        .map(|update| {
+            let account = journal_state.accounts.get(&update.account_id);
+            TransactionLine {
+                account_id: update.account_id,
+                account_name: account
+                    .map(|a| a.name.clone())
+                    .unwrap_or("unknown account".to_string()),
+                currency: account.map(|a| a.currency).unwrap_or_default(),
+                changed_by: update.changed_by,
+                memo: update.memo.clone(),
+            }
+        })
+        .collect();
+
+    Ok(TransactionDetail {
+        transaction: bundle_transaction(&journal_state, transaction, author),
+        lines,
+    })
+}
+
+/// every account's own balance at the end of `as_of`, with the debits and credits totalled
+/// per currency. entries are counted by their effective date
+#[server]
+pub async fn get_trial_balance(
+    journal_id: Uuid,
+    as_of: NaiveDate,
+) -> Result<TrialBalance, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    let journal_state = report_state(&journal_id, &pool).await?;
+    let tree = account_tree(&journal_state.accounts_between(None, as_of));
+
+    let accounts: Vec<Account> = AccountType::ALL
+        .into_iter()
+        .flat_map(|account_type| {
+            Account::flatten(
+                tree.iter()
+                    .filter(|account| account.account_type == account_type)
+                    .cloned()
+                    .collect(),
+            )
+        })
+        .filter(|account| account.balance != 0)
+        .collect();
+
+    let debits = currency_totals(
+        accounts
+            .iter()
+            .filter(|account| account.balance > 0)
+            .map(|account| (account.currency, account.balance)),
+    );
+    let credits = currency_totals(
+        accounts
+            .iter()
+            .filter(|account| account.balance < 0)
+            .map(|account| (account.currency, account.balance.saturating_neg())),
+    );
+
+    Ok(TrialBalance {
+        as_of,
+        accounts,
+        debits,
+        credits,
+    })
+}
+
+/// assets, liabilities and equity at the end of `as_of`
+#[server]
+pub async fn get_balance_sheet(
+    journal_id: Uuid,
+    as_of: NaiveDate,
+) -> Result<BalanceSheet, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    let journal_state = report_state(&journal_id, &pool).await?;
+    let accounts = journal_state.accounts_between(None, as_of);
+
+    Ok(BalanceSheet {
+        as_of,
+        sections: [
+            AccountType::Asset,
+            AccountType::Liability,
+            AccountType::Equity,
+        ]
+        .into_iter()
+        .map(|account_type| report_section(&accounts, account_type))
+        .collect(),
+        retained_earnings: net_income(&accounts),
+    })
+}
+
+/// income and expenses from entries dated `from` through `to`
+#[server]
+pub async fn get_income_statement(
+    journal_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<IncomeStatement, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    if from > to {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::InvalidInput.to_string()?,
+        ));
+    }
+
+    let journal_state = report_state(&journal_id, &pool).await?;
+    let accounts = journal_state.accounts_between(Some(from), to);
+
+    Ok(IncomeStatement {
+        from,
+        to,
+        sections: [AccountType::Income, AccountType::Expense]
+            .into_iter()
+            .map(|account_type| report_section(&accounts, account_type))
+            .collect(),
+        net_income: net_income(&accounts),
+    })
+}
+
+/// how many entries a page of an account's ledger holds
+const LEDGER_PAGE_SIZE: usize = 50;
+
+/// the entries that touch one account or its sub-accounts, newest first, with the rolled up
+/// balance after each. `page` counts from 0
+#[server]
+pub async fn get_account_ledger(
+    journal_id: Uuid,
+    account_id: Uuid,
+    #[server(default)] page: usize,
+) -> Result<AccountLedger, ServerFnError> {
+    let session_id = extensions::get_session_id().await?;
+    let pool = extensions::get_pool().await?;
+
+    let user_id = auth::get_user_id(&session_id, &pool).await?;
+
+    authorization::authorize(&user_id, &journal_id, Permissions::READ, &pool).await?;
+
+    let journal_state = report_state(&journal_id, &pool).await?;
+
+    let Some(account) = Account::find(account_tree(&journal_state.accounts), account_id) else {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::AccountNotFound.to_string()?,
+        ));
+    };
+
+    let subtree: HashSet<Uuid> = Account::flatten(vec![account.clone()])
+        .into_iter()
+        .map(|account| account.id)
+        .collect();
+
+    // each entry with its lines in the subtree
+    let mut entries: Vec<(&journal::Transaction, Vec<&journal::BalanceUpdate>)> = journal_state
+        .transactions
+        .iter()
+        .map(|transaction| {
+            let updates: Vec<&journal::BalanceUpdate> = transaction
+                .updates
+                .iter()
+                .filter(|update| subtree.contains(&update.account_id))
+                .collect();
+            (transaction, updates)
+        })
+        .filter(|(_, updates)| !updates.is_empty())
+        .collect();
+    // stable, so entries on the same day stay in the order they were entered
+    entries.sort_by_key(|(transaction, _)| transaction.effective_date);
+
+    let changes: Vec<i64> = entries
+        .iter()
+        .map(|(_, updates)| {
+            updates.iter().fold(0, |total: i64, update| {
+                total.saturating_add(update.changed_by)
+            })
+        })
+        .collect();
+
+    let mut running_balance: i64 = 0;
+    let running_balances: Vec<i64> = entries
+        .iter()
+        .zip(&changes)
+        .map(|((transaction, _), changed_by)| {
+            if !journal_state.is_reversed_in_place(&transaction.id) {
+                running_balance = running_balance.saturating_add(*changed_by);
+            }
+            running_balance
+        })
+        .collect();
+
+    let page_count = entries.len().div_ceil(LEDGER_PAGE_SIZE).max(1);
+
+    let account_name = |id: &Uuid| {
+        journal_state
+            .accounts
+            .get(id)
+            .map(|a| a.name.clone())
+            .unwrap_or("unknown account".to_string())
+    };
+
+    // a page is usually written by the same few people
+    let mut authors: HashMap<Uuid, String> = HashMap::new();
+    let mut lines = Vec::new();
+    for (((transaction, updates), changed_by), running_balance) in entries
+        .into_iter()
+        .zip(changes)
+        .zip(running_balances)
+        .rev()
+        .skip(page.saturating_mul(LEDGER_PAGE_SIZE))
+        .take(LEDGER_PAGE_SIZE)
+    {
+        let author = match authors.get(&transaction.author) {
+            Some(author) => author.clone(),
+            None => {
+                let author = username::get_username(&transaction.author, &pool)
+                    .await?
+                    .unwrap_or("unknown user".to_string());
+                _ = authors.insert(transaction.author, author.clone());
+                author
+            }
+        };
+
+        let memos: Vec<&str> = updates
+            .iter()
+            .filter_map(|update| update.memo.as_deref())
+            .collect();
+
+        lines.push(LedgerLine {
+            transaction_id: transaction.id,
+            effective_date: transaction.effective_date,
+            description: transaction.description.clone(),
+            author,
+            counter_accounts: transaction
+                .updates
+                .iter()
+                .filter(|other| !subtree.contains(&other.account_id))
+                .map(|other| account_name(&other.account_id))
+                .collect(),
+            sub_accounts: updates
+                .iter()
+                .filter(|update| update.account_id != account_id)
+                .map(|update| account_name(&update.account_id))
+                .collect(),
+            memo: (!memos.is_empty()).then(|| memos.join("; ")),
+            changed_by,
+            running_balance,
+            reversed: journal_state.reversals.contains_key(&transaction.id),
+        });
+    }
+
+    Ok(AccountLedger {
+        account,
+        lines,
+        page,
+        page_count,
+    })
+}
+
+/// nests the accounts under their parents and adds up each subtree's balance
+fn account_tree(accounts: &HashMap<Uuid, journal::AccountState>) -> Vec<Account> {
+    let mut children: HashMap<Option<Uuid>, Vec<Uuid>> = HashMap::new();
+    for (id, account) in accounts {
+        // an account whose parent is gone is shown at the top level
+        let parent = account
+            .parent
+            .filter(|parent| accounts.contains_key(parent));
+        children.entry(parent).or_default().push(*id);
+    }
+
+    fn build(
+        id: Uuid,
+        accounts: &HashMap<Uuid, journal::AccountState>,
+        children: &HashMap<Option<Uuid>, Vec<Uuid>>,
+    ) -> Option<Account> {
+        let account = accounts.get(&id)?;
+        let mut nested: Vec<Account> = children
+            .get(&Some(id))
+            .into_iter()
+            .flatten()
+            .filter_map(|child| build(*child, accounts, children))
+            .collect();
+        nested.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Some(Account {
+            id,
+            name: account.name.clone(),
+            currency: account.currency,
+            balance: account.balance,
+            archived: account.archived,
+            account_type: account.account_type,
+            parent: account.parent,
+            total_balance: nested.iter().fold(account.balance, |total, child| {
+                total.saturating_add(child.total_balance)
+            }),
+            children: nested,
+        })
+    }
+
+    let mut roots: Vec<Account> = children
+        .get(&None)
+        .into_iter()
+        .flatten()
+        .filter_map(|id| build(*id, accounts, &children))
+        .collect();
+    roots.sort_by(|a, b| a.name.cmp(&b.name));
+    roots
+}
+
+/// the accounts and entries the reports and ledgers are built from. doesn't check
+/// permissions, callers have to
+async fn report_state(journal_id: &Uuid, pool: &PgPool) -> Result<JournalState, ServerFnError> {
+    JournalState::build(
+        journal_id,
+        vec![
+            JournalEventType::CreatedAccount,
+            JournalEventType::DeletedAccount,
+            JournalEventType::AddedEntry,
+            JournalEventType::CreatedFxAccount,
+            JournalEventType::ReversedEntry,
+            JournalEventType::RenamedAccount,
+            JournalEventType::ArchivedAccount,
+            JournalEventType::MovedAccount,
+        ],
+        pool,
+    )
+    .await
+}
+
+/// adds up the amounts of each currency, sorted by currency code
+fn currency_totals(amounts: impl IntoIterator<Item = (Currency, i64)>) -> Vec<CurrencyTotal> {
+    let mut totals: HashMap<Currency, i64> = HashMap::new();
+    for (currency, amount) in amounts {
+        let total = totals.entry(currency).or_default();
+        *total = total.saturating_add(amount);
+    }
+
+    let mut totals: Vec<CurrencyTotal> = totals
+        .into_iter()
+        .map(|(currency, amount)| CurrencyTotal { currency, amount })
+        .collect();
+    totals.sort_by_key(|total| total.currency.code());
+    totals
+}
+
+/// the accounts of one type as a tree, leaving out the ones with nothing to show
+fn report_section(
+    accounts: &HashMap<Uuid, journal::AccountState>,
+    account_type: AccountType,
+) -> ReportSection {
+    fn prune(mut account: Account) -> Option<Account> {
+        account.children = std::mem::take(&mut account.children)
+            .into_iter()
+            .filter_map(prune)
+            .collect();
+        (account.total_balance != 0 || !account.children.is_empty()).then_some(account)
     }
 
-    let raw_transactions = sqlx::query_as::<_, (Vec<u8>, chrono::DateTime<Utc>)>(
-        r#"
-        SELECT payload, created_at FROM journal_events
-        WHERE journal_id = $1 AND event_type = $2
-        ORDER BY created_at ASC
-        "#,
+    // sub-accounts always share their parent's type, so only the top level is filtered
+    let accounts: Vec<Account> = account_tree(accounts)
+        .into_iter()
+        .filter(|account| account.account_type == account_type)
+        .filter_map(prune)
+        .collect();
+
+    let totals = currency_totals(accounts.iter().map(|account| {
+        (
+            account.currency,
+            account_type.normal_balance(account.total_balance),
+        )
+    }));
+
+    ReportSection {
+        account_type,
+        accounts,
+        totals,
+    }
+}
+
+/// income minus expenses for each currency that had any
+fn net_income(accounts: &HashMap<Uuid, journal::AccountState>) -> Vec<CurrencyTotal> {
+    currency_totals(
+        accounts
+            .values()
+            .filter(|account| {
+                account.balance != 0
+                    && matches!(
+                        account.account_type,
+                        AccountType::Income | AccountType::Expense
+                    )
+            })
+            // both are stored as debits minus credits, and income is a credit
+            .map(|account| (account.currency, account.balance.saturating_neg())),
+    )
+}
+
+/// the account tree as it stood at `as_of`. doesn't check permissions, callers have to
+async fn read_accounts(
+    journal_id: &Uuid,
+    as_of: journal::AsOf,
+    pool: &PgPool,
+) -> Result<Vec<Account>, ServerFnError> {
+    use journal::JournalEventType::*;
+
+    let journal_state = JournalState::build_as_of(
+        journal_id,
+        vec![
+            CreatedAccount,
+            DeletedAccount,
+            AddedEntry,
+            CreatedFxAccount,
+            ReversedEntry,
+            RenamedAccount,
+            ArchivedAccount,
+            MovedAccount,
+        ],
+        as_of,
+        pool,
+    )
+    .await?;
+
+    Ok(account_tree(&journal_state.accounts))
+}
+
+/// every entry recorded by `as_of` with its author's name and reversal, by effective date.
+/// doesn't check permissions, callers have to
+async fn read_transactions(
+    journal_id: &Uuid,
+    as_of: journal::AsOf,
+    pool: &PgPool,
+) -> Result<Vec<TransactionWithTimeStamp>, ServerFnError> {
+    let journal_state = JournalState::build_as_of(
+        journal_id,
+        vec![
+            JournalEventType::AddedEntry,
+            JournalEventType::ReversedEntry,
+        ],
+        as_of,
+        pool,
     )
-    .bind(journal_id)
-    .bind(journal::JournalEventType::AddedEntry)
-    .fetch_all(&pool)
     .await?;
 
-    let transactions: Vec<(Result<JournalEvent, ServerFnError>, chrono::DateTime<Utc>)> =
-        raw_transactions
+    // most journals only have a handful of authors
+    let mut authors: HashMap<Uuid, String> = HashMap::new();
+    let mut bundled_transactions = Vec::new();
+
+    for transaction in &journal_state.transactions {
+        let author = match authors.get(&transaction.author) {
+            Some(author) => author.clone(),
+            None => {
+                let author = username::get_username(&transaction.author, pool)
+                    .await?
+                    .unwrap_or("unknown user".to_string());
+                _ = authors.insert(transaction.author, author.clone());
+                author
+            }
+        };
+
+        bundled_transactions.push(bundle_transaction(&journal_state, transaction, author));
+    }
+
+    // stable, so entries on the same day stay in the order they were entered
+    bundled_transactions.sort_by_key(|t| t.transaction.effective_date);
+
+    Ok(bundled_transactions)
+}
+
+/// an entry of `journal_state` the way the transaction pages show it
+fn bundle_transaction(
+    journal_state: &JournalState,
+    transaction: &journal::Transaction,
+    author: String,
+) -> TransactionWithTimeStamp {
+    TransactionWithTimeStamp {
+        transaction: TransactionWithUsername {
+            id: transaction.id,
+            author,
+            description: transaction.description.clone(),
+            effective_date: transaction.effective_date,
+            updates: transaction.updates.clone(),
+            exchange_rates: transaction.exchange_rates.clone(),
+            corrects: transaction.corrects,
+            reverses: journal_state.offset_of(&transaction.id),
+            reversal: journal_state
+                .reversals
+                .get(&transaction.id)
+                .map(|reversal| ReversalInfo {
+                    reason: reversal.reason.clone(),
+                    replaced_by: reversal.replaced_by,
+                    offset: reversal.offset,
+                    reversed_at: reversal.reversed_at,
+                }),
+        },
+        timestamp: journal_state
+            .recorded_at
+            .get(&transaction.id)
+            .copied()
+            .unwrap_or_default(),
+    }
+}
+
+/// the entry `transaction_id` if it can still be reversed. entries that are themselves
+/// reversals can't be, correct the original instead
+fn reversible_entry<'a>(
+    journal_state: &'a JournalState,
+    transaction_id: &Uuid,
+) -> Result<&'a journal::Transaction, ServerFnError> {
+    let Some(transaction) = journal_state
+        .transactions
+        .iter()
+        .find(|t| t.id == *transaction_id)
+    else {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::TransactionNotFound.to_string()?,
+        ));
+    };
+
+    if journal_state.reversals.contains_key(transaction_id)
+        || journal_state.offset_of(transaction_id).is_some()
+    {
+        return Err(ServerFnError::ServerError(
+            KnownErrors::TransactionAlreadyReversed.to_string()?,
+        ));
+    }
+
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(
+        account_type: AccountType,
+        currency: Currency,
+        balance: i64,
+        parent: Option<Uuid>,
+    ) -> journal::AccountState {
+        journal::AccountState {
+            name: account_type.name().to_string(),
+            currency,
+            balance,
+            archived: false,
+            account_type,
+            parent,
+        }
+    }
+
+    fn totals(totals: &[CurrencyTotal]) -> Vec<(&'static str, i64)> {
+        totals
+            .iter()
+            .map(|total| (total.currency.code(), total.amount))
+            .collect()
+    }
+
+    #[test]
+    fn report_section_rolls_up_and_leaves_out_empty_accounts() {
+        let (food, groceries, empty, bank) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let accounts = HashMap::from([
+            (food, account(AccountType::Expense, Currency::Usd, 0, None)),
+            (
+                groceries,
+                account(AccountType::Expense, Currency::Usd, 2500, Some(food)),
+            ),
+            (empty, account(AccountType::Expense, Currency::Usd, 0, None)),
+            (
+                bank,
+                account(AccountType::Asset, Currency::Usd, -2500, None),
+            ),
+        ]);
+
+        let section = report_section(&accounts, AccountType::Expense);
+
+        let ids: Vec<Uuid> = Account::flatten(section.accounts.clone())
             .into_iter()
-            .map(|(transaction, timestamp)| {
+            .map(|account| account.id)
+            .collect();
+        assert_eq!(ids, vec![food, groceries]);
+        assert_eq!(section.accounts[0].total_balance, 2500);
+        assert_eq!(totals(&section.totals), vec![("USD", 2500)]);
+    }
+
+    #[test]
+    fn report_section_totals_are_on_the_normal_side() {
+        let accounts = HashMap::from([
+            (
+                Uuid::new_v4(),
+                account(AccountType::Liability, Currency::Usd, -2000, None),
+            ),
+            (
+                Uuid::new_v4(),
+                account(AccountType::Liability, Currency::Eur, 500, None),
+            ),
+        ]);
+
+        let section = report_section(&accounts, AccountType::Liability);
+
+        assert_eq!(totals(&section.totals), vec![("EUR", -500), ("USD", 2000)]);
+    }
+
+    #[test]
+    fn net_income_is_income_less_expenses_per_currency() {
+        let accounts = HashMap::from([
+            (
+                Uuid::new_v4(),
+                account(AccountType::Income, Currency::Usd, -5000, None),
+            ),
+            (
+                Uuid::new_v4(),
+                account(AccountType::Expense, Currency::Usd, 1500, None),
+            ),
+            (
+                Uuid::new_v4(),
+                account(AccountType::Expense, Currency::Eur, 300, None),
+            ),
+            (
+                Uuid::new_v4(),
+                account(AccountType::Income, Currency::Jpy, 0, None),
+            ),
+            (
+                Uuid::new_v4(),
+                account(AccountType::Asset, Currency::Usd, 3500, None),
+            ),
+        ]);
+
+        assert_eq!(
+            totals(&net_income(&accounts)),
+            vec![("EUR", -300), ("USD", 3500)]
+        );
+    }
+
+    #[test]
+    fn account_tree_rolls_up_sub_account_balances() {
+        let (food, groceries, produce, dining, bank) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let named = |name: &str, mut account: journal::AccountState| {
+            account.name = name.to_string();
+            account
+        };
+        let accounts = HashMap::from([
+            (
+                food,
+                named(
+                    "Food",
+                    account(AccountType::Expense, Currency::Usd, 100, None),
+                ),
+            ),
+            (
+                groceries,
+                named(
+                    "Groceries",
+                    account(AccountType::Expense, Currency::Usd, 2000, Some(food)),
+                ),
+            ),
+            (
+                produce,
+                named(
+                    "Produce",
+                    account(AccountType::Expense, Currency::Usd, 500, Some(groceries)),
+                ),
+            ),
+            (
+                dining,
+                named(
+                    "Dining",
+                    account(AccountType::Expense, Currency::Usd, 300, Some(food)),
+                ),
+            ),
+            (
+                bank,
+                named(
+                    "Bank",
+                    account(
+                        AccountType::Asset,
+                        Currency::Usd,
+                        -2900,
+                        Some(Uuid::new_v4()),
+                    ),
+                ),
+            ),
+        ]);
+
+        let tree = account_tree(&accounts);
+
+        // the bank's parent is gone, so it's at the top level
+        let roots: Vec<(&str, i64, i64)> = tree
+            .iter()
+            .map(|account| {
                 (
-                    from_bytes::<JournalEvent>(&transaction).map_err(|_| {
-                        ServerFnError::ServerError("failed to deserialize transaction".to_string())
-                    }),
-                    timestamp,
+                    account.name.as_str(),
+                    account.balance,
+                    account.total_balance,
                 )
             })
             .collect();
+        assert_eq!(roots, vec![("Bank", -2900, -2900), ("Food", 100, 2900)]);
 
-    for transaction in transactions {
-        let event: JournalEvent = transaction.0?;
-        let timestamp = transaction.1;
-
-        if let JournalEvent::AddedEntry { transaction } = event {
-            let author = username::get_username(&transaction.author, &pool)
-                .await?
-                .unwrap_or("unknown user".to_string());
-
-            bundled_transactions.push(TransactionWithTimeStamp {
-                transaction: TransactionWithUsername {
-                    author,
-                    updates: transaction.updates,
-                },
-                timestamp,
-            })
-        }
+        let children: Vec<(&str, i64)> = tree[1]
+            .children
+            .iter()
+            .map(|account| (account.name.as_str(), account.total_balance))
+            .collect();
+        assert_eq!(children, vec![("Dining", 300), ("Groceries", 2500)]);
+        assert_eq!(tree[1].children[1].children[0].total_balance, 500);
     }
-    Ok(bundled_transactions)
 }
@@ -9,3 +9,15 @@ pub mod extensions;
 #[allow(dead_code)]
 #[cfg(feature = "ssr")]
 pub mod return_types;
+
+#[allow(dead_code)]
+#[cfg(feature = "ssr")]
+pub mod money;
+
+#[allow(dead_code)]
+#[cfg(feature = "ssr")]
+pub mod entry;
+
+#[allow(dead_code)]
+#[cfg(feature = "ssr")]
+pub mod authorization;
@@ -0,0 +1,195 @@
+use super::return_types::KnownErrors;
+use crate::event_sourcing::currency::{Currency, RATE_DECIMALS};
+use leptos::prelude::ServerFnError;
+
+pub enum AmountError {
+    Invalid,
+    TooManyDecimalPlaces,
+    Negative,
+    Overflow,
+}
+
+/// parses a decimal string like "12.34" into a whole number of minor units without going
+/// through floating point. an empty field is treated as zero
+pub fn parse_minor_units(input: &str, minor_units: u32) -> Result<i64, AmountError> {
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Ok(0);
+    }
+
+    if input.starts_with('-') {
+        return Err(AmountError::Negative);
+    }
+
+    let (whole, fraction) = match input.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (input, ""),
+    };
+
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(AmountError::Invalid);
+    }
+
+    if fraction.len() > minor_units as usize {
+        return Err(AmountError::TooManyDecimalPlaces);
+    }
+
+    let digits_value = |digits: &str| {
+        digits.bytes().try_fold(0_i64, |total, digit| {
+            total
+                .checked_mul(10)
+                .and_then(|total| total.checked_add((digit - b'0') as i64))
+                .ok_or(AmountError::Overflow)
+        })
+    };
+
+    // with two minor digits "1.5" is 150, not 105
+    let fraction_value = digits_value(fraction)? * 10_i64.pow(minor_units - fraction.len() as u32);
+
+    digits_value(whole)?
+        .checked_mul(10_i64.pow(minor_units))
+        .and_then(|whole| whole.checked_add(fraction_value))
+        .ok_or(AmountError::Overflow)
+}
+
+/// parses an amount entered in the given row of a transaction form in the row's currency,
+/// reporting a `KnownErrors` variant that names the row if it's not a valid amount
+pub fn parse_row_amount(input: &str, row: usize, currency: Currency) -> Result<i64, ServerFnError> {
+    let amount = input.to_string();
+
+    let error = match parse_minor_units(input, currency.minor_units()) {
+        Ok(cents) => return Ok(cents),
+        Err(AmountError::Invalid) => KnownErrors::InvalidAmount { row, amount },
+        Err(AmountError::TooManyDecimalPlaces) => KnownErrors::TooManyDecimalPlaces {
+            row,
+            amount,
+            currency,
+        },
+        Err(AmountError::Negative) => KnownErrors::NegativeAmount { row, amount },
+        Err(AmountError::Overflow) => KnownErrors::AmountOverflow { row },
+    };
+
+    Err(ServerFnError::ServerError(error.to_string()?))
+}
+
+/// parses a rate like "1.0845" into the fixed point form `ExchangeRate` stores
+pub fn parse_rate(input: &str) -> Result<i64, ServerFnError> {
+    match parse_minor_units(input, RATE_DECIMALS) {
+        Ok(rate) if rate > 0 => Ok(rate),
+        _ => Err(ServerFnError::ServerError(
+            KnownErrors::InvalidExchangeRate {
+                rate: input.to_string(),
+            }
+            .to_string()?,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cents(input: &str) -> Result<i64, AmountError> {
+        parse_minor_units(input, Currency::Usd.minor_units())
+    }
+
+    #[test]
+    fn parses_decimals_exactly() {
+        assert!(matches!(cents("0.29"), Ok(29)));
+        assert!(matches!(cents("1.5"), Ok(150)));
+        assert!(matches!(cents("5"), Ok(500)));
+        assert!(matches!(
+            parse_minor_units("100", Currency::Jpy.minor_units()),
+            Ok(100)
+        ));
+    }
+
+    #[test]
+    fn accepts_a_missing_side_of_the_point() {
+        assert!(matches!(cents(".5"), Ok(50)));
+        assert!(matches!(cents("1."), Ok(100)));
+    }
+
+    #[test]
+    fn empty_is_zero() {
+        assert!(matches!(cents(""), Ok(0)));
+        assert!(matches!(cents("  "), Ok(0)));
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        assert!(matches!(cents("-1"), Err(AmountError::Negative)));
+        assert!(matches!(
+            cents("1.234"),
+            Err(AmountError::TooManyDecimalPlaces)
+        ));
+        assert!(matches!(cents("abc"), Err(AmountError::Invalid)));
+        assert!(matches!(cents("1e3"), Err(AmountError::Invalid)));
+        assert!(matches!(cents("."), Err(AmountError::Invalid)));
+        assert!(matches!(
+            parse_minor_units("1.5", Currency::Jpy.minor_units()),
+            Err(AmountError::TooManyDecimalPlaces)
+        ));
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert!(matches!(
+            cents("92233720368547758"),
+            Ok(9_223_372_036_854_775_800)
+        ));
+        // one more doesn't fit once it's scaled to cents
+        assert!(matches!(
+            cents("92233720368547759"),
+            Err(AmountError::Overflow)
+        ));
+        assert!(matches!(
+            cents("99999999999999999999"),
+            Err(AmountError::Overflow)
+        ));
+        assert!(matches!(
+            parse_minor_units("9223372036854775807", 0),
+            Ok(i64::MAX)
+        ));
+    }
+
+    #[test]
+    fn row_errors_name_the_row() {
+        let error = |input: &str| {
+            parse_row_amount(input, 3, Currency::Usd)
+                .err()
+                .and_then(|e| KnownErrors::parse_error(&e))
+        };
+
+        assert!(matches!(
+            parse_row_amount("12.34", 3, Currency::Usd),
+            Ok(1234)
+        ));
+        assert!(
+            error("abc")
+                == Some(KnownErrors::InvalidAmount {
+                    row: 3,
+                    amount: "abc".to_string(),
+                })
+        );
+        assert!(
+            error("1.234")
+                == Some(KnownErrors::TooManyDecimalPlaces {
+                    row: 3,
+                    amount: "1.234".to_string(),
+                    currency: Currency::Usd,
+                })
+        );
+        assert!(
+            error("-1")
+                == Some(KnownErrors::NegativeAmount {
+                    row: 3,
+                    amount: "-1".to_string(),
+                })
+        );
+        assert!(error("99999999999999999999") == Some(KnownErrors::AmountOverflow { row: 3 }));
+    }
+}
@@ -1,11 +1,12 @@
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use leptos::prelude::ServerFnError;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::event_sourcing::{
+    currency::{Currency, ExchangeRate},
     journal::JournalTenantInfo,
-    journal::{BalanceUpdate, Permissions},
+    journal::{AccountType, BalanceUpdate, Permissions},
 };
 
 #[derive(Serialize, Deserialize, PartialEq)]
@@ -36,6 +37,7 @@ pub enum KnownErrors {
 
     BalanceMismatch {
         attempted_transaction: Vec<BalanceUpdate>,
+        currency: Currency,
     },
 
     PermissionError {
@@ -50,7 +52,68 @@ pub enum KnownErrors {
 
     UserCanAccessJournal,
 
+    NotATenant,
+
     InvalidJournal,
+
+    ConcurrencyConflict,
+
+    InvalidAmount {
+        row: usize,
+        amount: String,
+    },
+
+    TooManyDecimalPlaces {
+        row: usize,
+        amount: String,
+        currency: Currency,
+    },
+
+    NegativeAmount {
+        row: usize,
+        amount: String,
+    },
+
+    AmountOverflow {
+        row: usize,
+    },
+
+    MissingExchangeRate {
+        from: Currency,
+        to: Currency,
+    },
+
+    InvalidExchangeRate {
+        rate: String,
+    },
+
+    TransactionNotFound,
+
+    TransactionAlreadyReversed,
+
+    RestorePeriodExpired,
+
+    AccountNotFound,
+
+    AccountArchived {
+        row: usize,
+    },
+
+    /// the row's account doesn't exist in the journal or was deleted
+    UnknownAccount {
+        row: usize,
+    },
+
+    DuplicateAccount {
+        row: usize,
+    },
+
+    /// the account has a balance or entries, so it can only be archived
+    AccountInUse,
+
+    AccountHasChildren,
+
+    InvalidParent,
 }
 
 impl KnownErrors {
@@ -72,7 +135,41 @@ impl KnownErrors {
 pub struct Account {
     pub id: Uuid,
     pub name: String,
+    pub currency: Currency,
+    /// debits minus credits, see `AccountType::normal_balance`
     pub balance: i64,
+    pub archived: bool,
+    pub account_type: AccountType,
+    pub parent: Option<Uuid>,
+    /// `balance` plus the totals of every sub-account
+    pub total_balance: i64,
+    /// sorted by name
+    pub children: Vec<Account>,
+}
+
+impl Account {
+    /// every account in the tree, each parent before its children
+    pub fn flatten(tree: Vec<Account>) -> Vec<Account> {
+        let mut accounts = Vec::new();
+        let mut stack: Vec<Account> = tree.into_iter().rev().collect();
+        while let Some(mut account) = stack.pop() {
+            stack.extend(std::mem::take(&mut account.children).into_iter().rev());
+            accounts.push(account);
+        }
+        accounts
+    }
+
+    /// the account `id` anywhere in the tree, with its sub-accounts still attached
+    pub fn find(tree: Vec<Account>, id: Uuid) -> Option<Account> {
+        let mut stack = tree;
+        while let Some(account) = stack.pop() {
+            if account.id == id {
+                return Some(account);
+            }
+            stack.extend(account.children);
+        }
+        None
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -91,7 +188,7 @@ pub enum AssociatedJournal {
 }
 
 impl AssociatedJournal {
-    fn has_permission(&self, permissions: Permissions) -> bool {
+    pub fn has_permission(&self, permissions: Permissions) -> bool {
         match self {
             Self::Owned { .. } => true,
             Self::Shared { tenant_info, .. } => {
@@ -128,6 +225,23 @@ pub struct Journals {
     pub selected: Option<AssociatedJournal>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DeletedJournal {
+    pub id: Uuid,
+    pub name: String,
+    pub deleted_at: chrono::DateTime<Utc>,
+    /// restoring fails after this
+    pub restorable_until: chrono::DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JournalMember {
+    pub id: Uuid,
+    pub username: String,
+    /// `None` for the owner
+    pub tenant_info: Option<JournalTenantInfo>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct JournalInvite {
     pub id: Uuid,
@@ -137,8 +251,25 @@ pub struct JournalInvite {
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct TransactionWithUsername {
+    pub id: Uuid,
     pub author: String,
+    pub description: String,
+    pub effective_date: NaiveDate,
     pub updates: Vec<BalanceUpdate>,
+    pub exchange_rates: Vec<ExchangeRate>,
+    pub corrects: Option<Uuid>,
+    /// the entry this one cancels out, if it was posted by a reversal
+    pub reverses: Option<Uuid>,
+    pub reversal: Option<ReversalInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReversalInfo {
+    pub reason: String,
+    pub replaced_by: Option<Uuid>,
+    /// the entry posted to cancel this one out, `None` for reversals from before those were
+    pub offset: Option<Uuid>,
+    pub reversed_at: chrono::DateTime<Utc>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -146,3 +277,96 @@ pub struct TransactionWithTimeStamp {
     pub transaction: TransactionWithUsername,
     pub timestamp: chrono::DateTime<Utc>,
 }
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TransactionLine {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub currency: Currency,
+    /// in the account currency's minor unit
+    pub changed_by: i64,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TransactionDetail {
+    pub transaction: TransactionWithTimeStamp,
+    pub lines: Vec<TransactionLine>,
+}
+
+/// a sum in one currency. the currencies of a journal are never added together
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CurrencyTotal {
+    pub currency: Currency,
+    pub amount: i64,
+}
+
+/// the accounts of one type on a report
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReportSection {
+    pub account_type: AccountType,
+    /// accounts that had nothing in the period are left out, unless one of their
+    /// sub-accounts did
+    pub accounts: Vec<Account>,
+    /// on the normal side for the section's type
+    pub totals: Vec<CurrencyTotal>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TrialBalance {
+    pub as_of: NaiveDate,
+    /// every account with a balance, each parent before its children. `balance` is the
+    /// account's own, sub-accounts are listed on their own lines
+    pub accounts: Vec<Account>,
+    pub debits: Vec<CurrencyTotal>,
+    pub credits: Vec<CurrencyTotal>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BalanceSheet {
+    pub as_of: NaiveDate,
+    /// assets, liabilities and equity, in that order
+    pub sections: Vec<ReportSection>,
+    /// income minus expenses up to `as_of`, which belongs to equity until it's closed out
+    pub retained_earnings: Vec<CurrencyTotal>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IncomeStatement {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// income, then expenses
+    pub sections: Vec<ReportSection>,
+    pub net_income: Vec<CurrencyTotal>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LedgerLine {
+    pub transaction_id: Uuid,
+    pub effective_date: NaiveDate,
+    pub description: String,
+    pub author: String,
+    /// the names of the entry's accounts outside the ledger's subtree
+    pub counter_accounts: Vec<String>,
+    /// the names of the sub-accounts it posted to, empty if only the account itself
+    pub sub_accounts: Vec<String>,
+    /// the memos of every line in the subtree
+    pub memo: Option<String>,
+    /// debits minus credits to the account and its sub-accounts
+    pub changed_by: i64,
+    /// the rolled up balance after this entry. entries reversed before reversals posted
+    /// an offsetting entry don't change it
+    pub running_balance: i64,
+    pub reversed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountLedger {
+    /// with its sub-accounts, whose entries are in the ledger too
+    pub account: Account,
+    /// newest first
+    pub lines: Vec<LedgerLine>,
+    /// counted from 0
+    pub page: usize,
+    pub page_count: usize,
+}
@@ -1,6 +1,6 @@
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use leptos::prelude::ServerFnError;
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 
 use crate::api::return_types::KnownErrors;
@@ -14,11 +14,11 @@ pub enum AuthEvent {
 }
 
 impl AuthEvent {
-    pub async fn push_db(
+    pub async fn push_db<'e>(
         &self,
         user_id: &Uuid,
         session_id: &String,
-        pool: &PgPool,
+        executor: impl PgExecutor<'e>,
     ) -> Result<i64, ServerFnError> {
         let session_bytes = URL_SAFE_NO_PAD.decode(session_id)?;
 
@@ -36,7 +36,7 @@ impl AuthEvent {
         .bind(user_id)
         .bind(session_bytes)
         .bind(self)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(id)
@@ -50,7 +50,7 @@ pub async fn get_user_id(session_id: &String, pool: &PgPool) -> Result<Uuid, Ser
         r#"
         SELECT user_id, event_type FROM auth_events
         WHERE session_id = $1
-        ORDER BY created_at DESC
+        ORDER BY id DESC
         LIMIT 1
         "#,
     )
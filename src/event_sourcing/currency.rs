@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+
+/// ISO 4217 currencies an account can be kept in.
+/// payloads store the variant index, so only ever append to this list
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    #[default]
+    Usd,
+    Eur,
+    Gbp,
+    Jpy,
+    Chf,
+    Cad,
+    Aud,
+    Cny,
+    Inr,
+    Krw,
+    Kwd,
+}
+
+impl Currency {
+    pub const ALL: [Currency; 11] = [
+        Self::Usd,
+        Self::Eur,
+        Self::Gbp,
+        Self::Jpy,
+        Self::Chf,
+        Self::Cad,
+        Self::Aud,
+        Self::Cny,
+        Self::Inr,
+        Self::Krw,
+        Self::Kwd,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Usd => "USD",
+            Self::Eur => "EUR",
+            Self::Gbp => "GBP",
+            Self::Jpy => "JPY",
+            Self::Chf => "CHF",
+            Self::Cad => "CAD",
+            Self::Aud => "AUD",
+            Self::Cny => "CNY",
+            Self::Inr => "INR",
+            Self::Krw => "KRW",
+            Self::Kwd => "KWD",
+        }
+    }
+
+    /// the ISO 4217 exponent, amounts are stored as whole numbers of this minor unit
+    pub fn minor_units(self) -> u32 {
+        match self {
+            Self::Jpy | Self::Krw => 0,
+            Self::Kwd => 3,
+            _ => 2,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Usd => "$",
+            Self::Eur => "€",
+            Self::Gbp => "£",
+            Self::Jpy => "¥",
+            Self::Chf => "CHF ",
+            Self::Cad => "CA$",
+            Self::Aud => "A$",
+            Self::Cny => "CN¥",
+            Self::Inr => "₹",
+            Self::Krw => "₩",
+            Self::Kwd => "KWD ",
+        }
+    }
+
+    /// formats an amount of minor units, e.g. 25043 cents as "$250.43" or 1500 yen as "¥1500"
+    pub fn format(self, amount: i64) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        format!(
+            "{}{}{}",
+            sign,
+            self.symbol(),
+            self.decimal(amount.unsigned_abs())
+        )
+    }
+
+    /// an amount of minor units without sign or symbol, the way the transaction form takes it
+    pub fn decimal(self, amount: u64) -> String {
+        match self.minor_units() {
+            0 => amount.to_string(),
+            digits => {
+                let scale = 10_u64.pow(digits);
+                format!(
+                    "{}.{:0width$}",
+                    amount / scale,
+                    amount % scale,
+                    width = digits as usize
+                )
+            }
+        }
+    }
+}
+
+/// rates are stored as fixed point numbers with this many decimal places
+pub const RATE_DECIMALS: u32 = 8;
+
+/// how many units of `quote` one unit of `base` is worth
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ExchangeRate {
+    pub base: Currency,
+    pub quote: Currency,
+    /// scaled by 10^RATE_DECIMALS, so 1.0845 is 108_450_000
+    pub rate: i64,
+}
+
+impl ExchangeRate {
+    /// converts minor units of either side of the pair into minor units of the other side,
+    /// rounding half away from zero. `None` if `from` isn't part of the pair or it overflows
+    pub fn convert(&self, amount: i64, from: Currency) -> Option<i64> {
+        let rate = i128::from(self.rate);
+        let base_scale = 10_i128.pow(RATE_DECIMALS + self.base.minor_units());
+        let quote_scale = 10_i128.pow(self.quote.minor_units());
+
+        let (numerator, denominator) = if from == self.base {
+            (rate.checked_mul(quote_scale)?, base_scale)
+        } else if from == self.quote {
+            (base_scale, rate.checked_mul(quote_scale)?)
+        } else {
+            return None;
+        };
+
+        if denominator == 0 {
+            return None;
+        }
+
+        let scaled = i128::from(amount).checked_mul(numerator)?;
+        let half = denominator / 2 * scaled.signum();
+        i64::try_from(scaled.checked_add(half)? / denominator).ok()
+    }
+
+    pub fn display(&self) -> String {
+        let scale = 10_i64.pow(RATE_DECIMALS);
+        let fraction = format!(
+            "{:0width$}",
+            self.rate % scale,
+            width = RATE_DECIMALS as usize
+        );
+        let fraction = fraction.trim_end_matches('0');
+        let fraction = if fraction.is_empty() { "0" } else { fraction };
+
+        format!(
+            "1 {} = {}.{} {}",
+            self.base.code(),
+            self.rate / scale,
+            fraction,
+            self.quote.code()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Currency, ExchangeRate};
+
+    fn rate(base: Currency, quote: Currency, rate: i64) -> ExchangeRate {
+        ExchangeRate { base, quote, rate }
+    }
+
+    #[test]
+    fn minor_units_follow_iso_4217() {
+        assert_eq!(Currency::Jpy.minor_units(), 0);
+        assert_eq!(Currency::Krw.minor_units(), 0);
+        assert_eq!(Currency::Usd.minor_units(), 2);
+        assert_eq!(Currency::Eur.minor_units(), 2);
+        assert_eq!(Currency::Kwd.minor_units(), 3);
+    }
+
+    #[test]
+    fn format_places_the_decimal_point_by_exponent() {
+        assert_eq!(Currency::Usd.format(25043), "$250.43");
+        assert_eq!(Currency::Usd.format(-5), "-$0.05");
+        assert_eq!(Currency::Eur.format(0), "€0.00");
+        assert_eq!(Currency::Jpy.format(1500), "¥1500");
+        assert_eq!(Currency::Jpy.format(-1500), "-¥1500");
+        assert_eq!(Currency::Kwd.format(1005), "KWD 1.005");
+    }
+
+    #[test]
+    fn decimal_is_what_the_form_parses() {
+        assert_eq!(Currency::Usd.decimal(25043), "250.43");
+        assert_eq!(Currency::Usd.decimal(7), "0.07");
+        assert_eq!(Currency::Jpy.decimal(1500), "1500");
+        assert_eq!(Currency::Kwd.decimal(1005), "1.005");
+    }
+
+    #[test]
+    fn convert_rounds_half_away_from_zero() {
+        // 1 EUR = 1.5 USD, so a cent is worth a cent and a half
+        let pair = rate(Currency::Eur, Currency::Usd, 150_000_000);
+        assert_eq!(pair.convert(1, Currency::Eur), Some(2));
+        assert_eq!(pair.convert(-1, Currency::Eur), Some(-2));
+        assert_eq!(pair.convert(3, Currency::Eur), Some(5));
+        assert_eq!(pair.convert(-3, Currency::Eur), Some(-5));
+
+        // 1.25 cents rounds down in both signs
+        let pair = rate(Currency::Eur, Currency::Usd, 125_000_000);
+        assert_eq!(pair.convert(1, Currency::Eur), Some(1));
+        assert_eq!(pair.convert(-1, Currency::Eur), Some(-1));
+    }
+
+    #[test]
+    fn convert_goes_both_ways_across_the_pair() {
+        let pair = rate(Currency::Eur, Currency::Usd, 109_500_000);
+        assert_eq!(pair.convert(100, Currency::Eur), Some(110));
+        assert_eq!(pair.convert(110, Currency::Usd), Some(100));
+        assert_eq!(pair.convert(100, Currency::Gbp), None);
+    }
+
+    #[test]
+    fn convert_scales_between_exponents() {
+        // USD (2) to JPY (0): 1 USD = 150.25 JPY
+        let usd_jpy = rate(Currency::Usd, Currency::Jpy, 15_025_000_000);
+        assert_eq!(usd_jpy.convert(100, Currency::Usd), Some(150));
+        assert_eq!(usd_jpy.convert(302, Currency::Usd), Some(454));
+        assert_eq!(usd_jpy.convert(-302, Currency::Usd), Some(-454));
+        assert_eq!(usd_jpy.convert(150, Currency::Jpy), Some(100));
+
+        // KWD (3) to USD (2): 1 KWD = 3.25 USD
+        let kwd_usd = rate(Currency::Kwd, Currency::Usd, 325_000_000);
+        assert_eq!(kwd_usd.convert(1_000, Currency::Kwd), Some(325));
+        assert_eq!(kwd_usd.convert(1, Currency::Kwd), Some(0));
+        assert_eq!(kwd_usd.convert(2, Currency::Kwd), Some(1));
+        assert_eq!(kwd_usd.convert(100, Currency::Usd), Some(308));
+
+        // KWD (3) to JPY (0): 1 KWD = 490.5 JPY
+        let kwd_jpy = rate(Currency::Kwd, Currency::Jpy, 49_050_000_000);
+        assert_eq!(kwd_jpy.convert(1_000, Currency::Kwd), Some(491));
+        assert_eq!(kwd_jpy.convert(-1_000, Currency::Kwd), Some(-491));
+        assert_eq!(kwd_jpy.convert(3, Currency::Kwd), Some(1));
+        assert_eq!(kwd_jpy.convert(491, Currency::Jpy), Some(1_001));
+    }
+
+    #[test]
+    fn convert_refuses_overflow() {
+        let pair = rate(Currency::Eur, Currency::Usd, 200_000_000);
+        assert_eq!(pair.convert(i64::MAX, Currency::Eur), None);
+    }
+
+    #[test]
+    fn display_trims_trailing_zeros() {
+        assert_eq!(
+            rate(Currency::Eur, Currency::Usd, 109_500_000).display(),
+            "1 EUR = 1.095 USD"
+        );
+        assert_eq!(
+            rate(Currency::Eur, Currency::Usd, 200_000_000).display(),
+            "1 EUR = 2.0 USD"
+        );
+        assert_eq!(
+            rate(Currency::Usd, Currency::Jpy, 15_025_000_000).display(),
+            "1 USD = 150.25 JPY"
+        );
+        assert_eq!(
+            rate(Currency::Jpy, Currency::Kwd, 1).display(),
+            "1 JPY = 0.00000001 KWD"
+        );
+    }
+}
@@ -1,12 +1,26 @@
 use bitflags::bitflags;
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use leptos::prelude::ServerFnError;
-use postcard::{from_bytes, to_allocvec};
+use postcard::to_allocvec;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, query_as, query_scalar};
+use sqlx::{PgExecutor, PgPool, query_scalar};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use super::currency::{Currency, ExchangeRate};
+use super::snapshot::{self, AggregateType, SNAPSHOT_INTERVAL};
+use super::stream::{self, Stream};
+use super::upcast::{StoredEvent, UpcasterRegistry};
+use crate::api::return_types::KnownErrors;
+
+mod upcasters;
+
+/// how long a deleted journal can still be restored
+pub const RESTORE_GRACE_DAYS: i64 = 30;
+
+/// bump this whenever the layout of `JournalState` changes so old snapshots are ignored
+const SNAPSHOT_VERSION: i16 = 11;
+
 bitflags! {
     #[derive(Serialize, Deserialize, Hash, Default, Debug, Clone, Copy, PartialEq)]
     pub struct Permissions: i16 {
@@ -18,29 +32,155 @@ bitflags! {
     }
 }
 
+/// decides which side of an account is its normal balance.
+/// payloads store the variant index, so only ever append to this list
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AccountType {
+    #[default]
+    Asset,
+    Liability,
+    Equity,
+    Income,
+    Expense,
+}
+
+impl AccountType {
+    /// in the order a chart of accounts lists them
+    pub const ALL: [AccountType; 5] = [
+        Self::Asset,
+        Self::Liability,
+        Self::Equity,
+        Self::Income,
+        Self::Expense,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Asset => "Asset",
+            Self::Liability => "Liability",
+            Self::Equity => "Equity",
+            Self::Income => "Income",
+            Self::Expense => "Expense",
+        }
+    }
+
+    /// the plural used as a heading for a group of accounts
+    pub fn plural(self) -> &'static str {
+        match self {
+            Self::Asset => "Assets",
+            Self::Liability => "Liabilities",
+            Self::Equity => "Equity",
+            Self::Income => "Income",
+            Self::Expense => "Expenses",
+        }
+    }
+
+    /// whether debits increase the account. credits increase the others
+    pub fn is_debit_normal(self) -> bool {
+        matches!(self, Self::Asset | Self::Expense)
+    }
+
+    /// balances are stored as debits minus credits. this flips the sign for credit normal
+    /// accounts, so the result is positive when the account is on its normal side
+    pub fn normal_balance(self, balance: i64) -> i64 {
+        if self.is_debit_normal() {
+            balance
+        } else {
+            balance.saturating_neg()
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct BalanceUpdate {
     pub account_id: Uuid,
     pub changed_by: i64,
+    pub memo: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Transaction {
+    pub id: Uuid,
     pub author: Uuid,
+    pub description: String,
+    /// when it happened, as opposed to when it was entered
+    pub effective_date: NaiveDate,
     pub updates: Vec<BalanceUpdate>,
+    /// the rates the entry was balanced with, empty unless it mixes currencies
+    pub exchange_rates: Vec<ExchangeRate>,
+    /// the reversed entry this one replaces, if it was posted as a correction
+    pub corrects: Option<Uuid>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum JournalEvent {
-    Created { name: String, owner: Uuid },
-    Renamed { name: String },
-    CreatedAccount { account_name: String },
-    DeletedAccount { account_id: Uuid },
-    AddedEntry { transaction: Transaction },
+    Created {
+        name: String,
+        owner: Uuid,
+    },
+    Renamed {
+        name: String,
+    },
+    CreatedAccount {
+        id: Uuid,
+        account_name: String,
+        currency: Currency,
+        account_type: AccountType,
+    },
+    DeletedAccount {
+        account_id: Uuid,
+    },
+    AddedEntry {
+        transaction: Transaction,
+    },
     Deleted,
+    /// replaces the journal's rate for the pair, in either direction
+    RecordedExchangeRate {
+        rate: ExchangeRate,
+    },
+    /// the account exchange differences in `currency` are posted to
+    CreatedFxAccount {
+        id: Uuid,
+        currency: Currency,
+    },
+    /// marks the entry as reversed, it stays in the history. `offset` is the entry posted
+    /// right after this with every line negated
+    ReversedEntry {
+        entry_id: Uuid,
+        reason: String,
+        offset: Option<Uuid>,
+    },
+    /// undoes `Deleted`, only allowed within `RESTORE_GRACE_DAYS`
+    Restored,
+    RenamedAccount {
+        account_id: Uuid,
+        name: String,
+    },
+    /// hides the account and stops new postings to it, its history stays intact
+    ArchivedAccount {
+        account_id: Uuid,
+    },
+    /// puts the account under `parent`, or at the top level if it's `None`
+    MovedAccount {
+        account_id: Uuid,
+        parent: Option<Uuid>,
+    },
 }
 
-#[derive(sqlx::Type)]
+const EVENT_REGISTRY: UpcasterRegistry<JournalEvent> = UpcasterRegistry {
+    current: 7,
+    upcasters: &[
+        (0, upcasters::from_v0),
+        (1, upcasters::from_v1),
+        (2, upcasters::from_v2),
+        (3, upcasters::from_v3),
+        (4, upcasters::from_v4),
+        (5, upcasters::from_v5),
+        (6, upcasters::from_v6),
+    ],
+};
+
+#[derive(sqlx::Type, Clone, Copy)]
 #[sqlx(type_name = "smallint")]
 #[repr(i16)]
 pub enum JournalEventType {
@@ -50,6 +190,13 @@ pub enum JournalEventType {
     DeletedAccount = 4,
     AddedEntry = 5,
     Deleted = 6,
+    RecordedExchangeRate = 7,
+    CreatedFxAccount = 8,
+    ReversedEntry = 9,
+    Restored = 10,
+    RenamedAccount = 11,
+    ArchivedAccount = 12,
+    MovedAccount = 13,
 }
 
 impl JournalEvent {
@@ -62,42 +209,131 @@ impl JournalEvent {
             Self::DeletedAccount { .. } => DeletedAccount,
             Self::AddedEntry { .. } => AddedEntry,
             Self::Deleted => Deleted,
+            Self::RecordedExchangeRate { .. } => RecordedExchangeRate,
+            Self::CreatedFxAccount { .. } => CreatedFxAccount,
+            Self::ReversedEntry { .. } => ReversedEntry,
+            Self::Restored => Restored,
+            Self::RenamedAccount { .. } => RenamedAccount,
+            Self::ArchivedAccount { .. } => ArchivedAccount,
+            Self::MovedAccount { .. } => MovedAccount,
         }
     }
 
-    pub async fn push_db(&self, uuid: &Uuid, pool: &PgPool) -> Result<i64, ServerFnError> {
+    pub fn decode(event: &StoredEvent) -> Result<Self, ServerFnError> {
+        EVENT_REGISTRY.decode(event)
+    }
+
+    pub async fn push_db<'e>(
+        &self,
+        uuid: &Uuid,
+        expected_sequence: i64,
+        executor: impl PgExecutor<'e>,
+    ) -> Result<i64, ServerFnError> {
         let payload: Vec<u8> = to_allocvec(self)?;
 
-        let id: i64 = sqlx::query_scalar(
+        let id: Result<i64, sqlx::Error> = sqlx::query_scalar(
             r#"
             INSERT INTO journal_events (
                 journal_id,
+                sequence,
                 event_type,
+                schema_version,
                 payload
             )
-            VALUES ($1, $2, $3)
+            VALUES ($1, $2, $3, $4, $5)
             RETURNING id
             "#,
         )
         .bind(uuid)
+        .bind(expected_sequence + 1)
         .bind(self.get_type())
+        .bind(EVENT_REGISTRY.current)
         .bind(payload)
-        .fetch_one(pool)
-        .await?;
+        .fetch_one(executor)
+        .await;
 
-        Ok(id)
+        match id {
+            Ok(id) => Ok(id),
+            Err(e) if stream::is_conflict(&e) => Err(ServerFnError::ServerError(
+                KnownErrors::ConcurrencyConflict.to_string()?,
+            )),
+            Err(e) => Err(e.into()),
+        }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AccountState {
+    pub name: String,
+    pub currency: Currency,
+    /// in the currency's minor unit
+    pub balance: i64,
+    pub archived: bool,
+    pub account_type: AccountType,
+    pub parent: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Reversal {
+    pub reason: String,
+    /// the entry posted in its place, if it was corrected rather than just reversed
+    pub replaced_by: Option<Uuid>,
+    pub reversed_at: chrono::DateTime<Utc>,
+    /// the entry that cancels this one out. reversals recorded before those were posted
+    /// don't have one, their balances are offset directly instead
+    pub offset: Option<Uuid>,
+}
+
 #[derive(Default, Serialize, Deserialize)]
 pub struct JournalState {
     pub id: Uuid,
     pub name: String,
     pub created_at: chrono::DateTime<Utc>,
     pub owner: Uuid,
-    pub accounts: HashMap<Uuid, (String, i64)>,
+    pub accounts: HashMap<Uuid, AccountState>,
     pub transactions: Vec<Transaction>,
+    /// when each entry was recorded, by id
+    pub recorded_at: HashMap<Uuid, chrono::DateTime<Utc>>,
+    pub reversals: HashMap<Uuid, Reversal>,
     pub deleted: bool,
+    /// the latest rate for each currency pair, keyed by the order it was recorded in
+    pub exchange_rates: HashMap<(Currency, Currency), ExchangeRate>,
+    pub fx_accounts: HashMap<Currency, Uuid>,
+    /// the stream version this state was read at, pass it to `push_db` as the expected sequence
+    #[serde(skip)]
+    pub sequence: i64,
+}
+
+/// how much of a journal's history to replay
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AsOf {
+    /// everything recorded so far
+    Latest,
+    /// the events recorded before this moment, so the start of a day leaves that day out
+    Time(chrono::DateTime<Utc>),
+}
+
+impl AsOf {
+    /// the last sequence number to replay, `None` for all of them
+    pub async fn cutoff(self, id: &Uuid, pool: &PgPool) -> Result<Option<i64>, ServerFnError> {
+        match self {
+            Self::Latest => Ok(None),
+            Self::Time(time) => {
+                let sequence: Option<i64> = query_scalar(
+                    r#"
+                        SELECT MAX(sequence) FROM journal_events
+                        WHERE journal_id = $1 AND created_at < $2
+                    "#,
+                )
+                .bind(id)
+                .bind(time)
+                .fetch_one(pool)
+                .await?;
+
+                Ok(Some(sequence.unwrap_or(0)))
+            }
+        }
+    }
 }
 
 impl JournalState {
@@ -106,17 +342,63 @@ impl JournalState {
         event_types: Vec<JournalEventType>,
         pool: &PgPool,
     ) -> Result<Self, ServerFnError> {
-        let journal_events = query_as::<_, (Vec<u8>,)>(
-            r#"
-                SELECT payload FROM journal_events
-                WHERE journal_id = $1 AND event_type = ANY($2)
-                ORDER BY created_at ASC
-                "#,
+        Self::build_as_of(id, event_types, AsOf::Latest, pool).await
+    }
+
+    /// the journal as it stood at `as_of`. snapshots taken after that point are ignored,
+    /// and historical replays don't save any
+    pub async fn build_as_of(
+        id: &Uuid,
+        event_types: Vec<JournalEventType>,
+        as_of: AsOf,
+        pool: &PgPool,
+    ) -> Result<Self, ServerFnError> {
+        let event_types: Vec<i16> = event_types.into_iter().map(|t| t as i16).collect();
+        let event_filter = snapshot::event_filter(event_types.iter().copied());
+        let cutoff = as_of.cutoff(id, pool).await?;
+
+        let snapshot = snapshot::load::<Self>(
+            id,
+            AggregateType::Journal,
+            event_filter,
+            SNAPSHOT_VERSION,
+            pool,
         )
-        .bind(id)
-        .bind(event_types)
-        .fetch_all(pool)
-        .await?;
+        .await?
+        .filter(|s| cutoff.is_none_or(|cutoff| s.last_sequence <= cutoff));
+
+        let (mut aggregate, mut last_sequence) = match snapshot {
+            Some(s) => (s.aggregate, s.last_sequence),
+            None => (
+                Self {
+                    id: *id,
+                    ..Default::default()
+                },
+                0,
+            ),
+        };
+
+        let (sequence, journal_events) = match cutoff {
+            None => {
+                // read before the events so a concurrent append can only cause a spurious conflict
+                let sequence = current_sequence(id, pool).await?;
+                let events =
+                    stream::read(Stream::Journal, id, &event_types, last_sequence, pool).await?;
+                (sequence, events)
+            }
+            Some(cutoff) => {
+                let events = stream::read_until(
+                    Stream::Journal,
+                    id,
+                    &event_types,
+                    last_sequence,
+                    cutoff,
+                    pool,
+                )
+                .await?;
+                (cutoff, events)
+            }
+        };
 
         let created_at: Option<chrono::DateTime<Utc>> = query_scalar(
             r#"
@@ -129,23 +411,34 @@ impl JournalState {
         .fetch_optional(pool)
         .await?;
 
-        let mut aggregate = Self {
-            id: *id,
-            created_at: created_at.unwrap_or_default(),
-            ..Default::default()
-        };
+        aggregate.created_at = created_at.unwrap_or_default();
+
+        let replayed = journal_events.len();
+        aggregate.sequence = sequence;
+
+        for event in journal_events {
+            aggregate.apply(JournalEvent::decode(&event.stored())?, event.created_at);
+            last_sequence = event.sequence;
+        }
 
-        journal_events
-            .into_iter()
-            .try_for_each(|(payload,)| -> Result<(), ServerFnError> {
-                aggregate.apply(from_bytes::<JournalEvent>(&payload)?);
-                Ok(())
-            })?;
+        if cutoff.is_none() && replayed >= SNAPSHOT_INTERVAL {
+            snapshot::save(
+                id,
+                AggregateType::Journal,
+                event_filter,
+                SNAPSHOT_VERSION,
+                &aggregate,
+                last_sequence,
+                pool,
+            )
+            .await?;
+        }
 
         Ok(aggregate)
     }
 
-    pub fn apply(&mut self, event: JournalEvent) {
+    /// `recorded_at` is when the event was stored, or now for one that's about to be
+    pub fn apply(&mut self, event: JournalEvent, recorded_at: chrono::DateTime<Utc>) {
         match event {
             JournalEvent::Created { name, owner } => {
                 self.name = name;
@@ -154,8 +447,23 @@ impl JournalState {
 
             JournalEvent::Renamed { name } => self.name = name,
 
-            JournalEvent::CreatedAccount { account_name } => {
-                _ = self.accounts.insert(Uuid::new_v4(), (account_name, 0))
+            JournalEvent::CreatedAccount {
+                id,
+                account_name,
+                currency,
+                account_type,
+            } => {
+                _ = self.accounts.insert(
+                    id,
+                    AccountState {
+                        name: account_name,
+                        currency,
+                        balance: 0,
+                        archived: false,
+                        account_type,
+                        parent: None,
+                    },
+                )
             }
             JournalEvent::DeletedAccount { account_id } => {
                 _ = self.accounts.remove(&account_id);
@@ -164,13 +472,181 @@ impl JournalState {
                 for balance_update in &transaction.updates {
                     self.accounts
                         .entry(balance_update.account_id)
-                        .and_modify(|(_, balance)| *balance += balance_update.changed_by);
+                        .and_modify(|account| account.balance += balance_update.changed_by);
                 }
+                if let Some(reversal) = transaction
+                    .corrects
+                    .and_then(|original| self.reversals.get_mut(&original))
+                {
+                    reversal.replaced_by = Some(transaction.id);
+                }
+                _ = self.recorded_at.insert(transaction.id, recorded_at);
                 self.transactions.push(transaction);
             }
             JournalEvent::Deleted => self.deleted = true,
+            JournalEvent::Restored => self.deleted = false,
+            JournalEvent::RenamedAccount { account_id, name } => {
+                if let Some(account) = self.accounts.get_mut(&account_id) {
+                    account.name = name;
+                }
+            }
+            JournalEvent::ArchivedAccount { account_id } => {
+                if let Some(account) = self.accounts.get_mut(&account_id) {
+                    account.archived = true;
+                }
+            }
+            JournalEvent::MovedAccount { account_id, parent } => {
+                if let Some(account) = self.accounts.get_mut(&account_id) {
+                    account.parent = parent;
+                }
+            }
+            JournalEvent::RecordedExchangeRate { rate } => {
+                _ = self.exchange_rates.remove(&(rate.quote, rate.base));
+                _ = self.exchange_rates.insert((rate.base, rate.quote), rate);
+            }
+            JournalEvent::CreatedFxAccount { id, currency } => {
+                _ = self.accounts.insert(
+                    id,
+                    AccountState {
+                        name: format!("FX gain/loss ({})", currency.code()),
+                        currency,
+                        balance: 0,
+                        archived: false,
+                        // exchange gains are credits, like any other income
+                        account_type: AccountType::Income,
+                        parent: None,
+                    },
+                );
+                _ = self.fx_accounts.insert(currency, id);
+            }
+            JournalEvent::ReversedEntry {
+                entry_id,
+                reason,
+                offset,
+            } => {
+                if offset.is_none()
+                    && let Some(original) = self.transactions.iter().find(|t| t.id == entry_id)
+                {
+                    for balance_update in &original.updates {
+                        self.accounts
+                            .entry(balance_update.account_id)
+                            .and_modify(|account| account.balance -= balance_update.changed_by);
+                    }
+                }
+                _ = self.reversals.insert(
+                    entry_id,
+                    Reversal {
+                        reason,
+                        replaced_by: None,
+                        reversed_at: recorded_at,
+                        offset,
+                    },
+                );
+            }
         }
     }
+
+    /// the accounts with only the entries dated `from` through `to` in their balances, for
+    /// reports. needs the account events along with `AddedEntry` and `ReversedEntry`
+    pub fn accounts_between(
+        &self,
+        from: Option<NaiveDate>,
+        to: NaiveDate,
+    ) -> HashMap<Uuid, AccountState> {
+        let mut accounts = self.accounts.clone();
+        for account in accounts.values_mut() {
+            account.balance = 0;
+        }
+
+        let in_range = |date: NaiveDate| from.is_none_or(|from| date >= from) && date <= to;
+
+        for transaction in &self.transactions {
+            // reversals without an offsetting entry take it out of the balances on the day
+            // they were made, or on the entry's own date if that's later
+            let reversed_on = self
+                .reversals
+                .get(&transaction.id)
+                .filter(|reversal| reversal.offset.is_none())
+                .map(|reversal| {
+                    reversal
+                        .reversed_at
+                        .date_naive()
+                        .max(transaction.effective_date)
+                });
+
+            let sign = match (
+                in_range(transaction.effective_date),
+                reversed_on.is_some_and(in_range),
+            ) {
+                (true, false) => 1,
+                (false, true) => -1,
+                _ => continue,
+            };
+
+            for balance_update in &transaction.updates {
+                if let Some(account) = accounts.get_mut(&balance_update.account_id) {
+                    account.balance = account
+                        .balance
+                        .saturating_add(balance_update.changed_by.saturating_mul(sign));
+                }
+            }
+        }
+
+        accounts
+    }
+
+    /// whether the entry was reversed before reversals posted an offsetting entry, so
+    /// that it no longer counts towards any balance
+    pub fn is_reversed_in_place(&self, entry_id: &Uuid) -> bool {
+        self.reversals
+            .get(entry_id)
+            .is_some_and(|reversal| reversal.offset.is_none())
+    }
+
+    /// the entry `offset_id` cancels out, if it was posted by a reversal
+    pub fn offset_of(&self, offset_id: &Uuid) -> Option<Uuid> {
+        self.reversals
+            .iter()
+            .find(|(_, reversal)| reversal.offset == Some(*offset_id))
+            .map(|(entry_id, _)| *entry_id)
+    }
+
+    /// whether `account_id` can be moved under `parent_id`. the parent has to be an active
+    /// account of the same type and currency, so its balance can include the child's, and
+    /// can't be the account itself or one of its descendants
+    pub fn can_parent(&self, account_id: &Uuid, parent_id: &Uuid) -> bool {
+        let (Some(account), Some(parent)) =
+            (self.accounts.get(account_id), self.accounts.get(parent_id))
+        else {
+            return false;
+        };
+
+        if parent.archived
+            || parent.account_type != account.account_type
+            || parent.currency != account.currency
+        {
+            return false;
+        }
+
+        // walk up from the parent, bounded in case the history already holds a cycle
+        let mut ancestor = Some(*parent_id);
+        for _ in 0..=self.accounts.len() {
+            match ancestor {
+                Some(id) if id == *account_id => return false,
+                Some(id) => ancestor = self.accounts.get(&id).and_then(|a| a.parent),
+                None => return true,
+            }
+        }
+        false
+    }
+
+    /// the rate between two currencies regardless of which way round it was recorded
+    pub fn exchange_rate(&self, a: Currency, b: Currency) -> Option<ExchangeRate> {
+        self.exchange_rates
+            .get(&(a, b))
+            .or_else(|| self.exchange_rates.get(&(b, a)))
+            .copied()
+    }
 }
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug)]
@@ -202,3 +678,279 @@ pub async fn get_name_from_id(id: &Uuid, pool: &PgPool) -> Result<Option<String>
     }
     Ok(Some(journal_state.name))
 }
+
+/// when the journal was last deleted, or `None` if it never was
+pub async fn deleted_at(
+    id: &Uuid,
+    pool: &PgPool,
+) -> Result<Option<chrono::DateTime<Utc>>, ServerFnError> {
+    Ok(query_scalar(
+        r#"
+            SELECT MAX(created_at) FROM journal_events
+            WHERE journal_id = $1 AND event_type = $2
+        "#,
+    )
+    .bind(id)
+    .bind(JournalEventType::Deleted)
+    .fetch_one(pool)
+    .await?)
+}
+
+/// the sequence number of the latest event in the journal's stream, or 0 if it has none
+pub async fn current_sequence(id: &Uuid, pool: &PgPool) -> Result<i64, ServerFnError> {
+    stream::current_sequence(Stream::Journal, id, pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, NaiveTime};
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap_or_default()
+    }
+
+    fn at(month: u32, day: u32) -> DateTime<Utc> {
+        date(month, day).and_time(NaiveTime::MIN).and_utc()
+    }
+
+    fn account(id: Uuid, account_type: AccountType) -> JournalEvent {
+        JournalEvent::CreatedAccount {
+            id,
+            account_name: account_type.name().to_string(),
+            currency: Currency::Usd,
+            account_type,
+        }
+    }
+
+    /// moves `amount` from `credit` to `debit`
+    fn entry(
+        id: Uuid,
+        effective_date: NaiveDate,
+        debit: Uuid,
+        credit: Uuid,
+        amount: i64,
+    ) -> JournalEvent {
+        JournalEvent::AddedEntry {
+            transaction: Transaction {
+                id,
+                author: Uuid::nil(),
+                description: String::new(),
+                effective_date,
+                updates: vec![
+                    BalanceUpdate {
+                        account_id: debit,
+                        changed_by: amount,
+                        memo: None,
+                    },
+                    BalanceUpdate {
+                        account_id: credit,
+                        changed_by: -amount,
+                        memo: None,
+                    },
+                ],
+                exchange_rates: Vec::new(),
+                corrects: None,
+            },
+        }
+    }
+
+    /// a bank account and an income account, with 100 earned on 1 february
+    fn earned() -> (JournalState, Uuid, Uuid, Uuid) {
+        let (bank, income, entry_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut state = JournalState::default();
+        state.apply(account(bank, AccountType::Asset), at(1, 1));
+        state.apply(account(income, AccountType::Income), at(1, 1));
+        state.apply(entry(entry_id, date(2, 1), bank, income, 100), at(2, 1));
+        (state, bank, income, entry_id)
+    }
+
+    fn balance(accounts: &HashMap<Uuid, AccountState>, id: &Uuid) -> Option<i64> {
+        accounts.get(id).map(|account| account.balance)
+    }
+
+    #[test]
+    fn accounts_between_only_counts_entries_in_the_range() {
+        let (state, bank, income, _) = earned();
+
+        let accounts = state.accounts_between(None, date(2, 1));
+        assert_eq!(balance(&accounts, &bank), Some(100));
+        assert_eq!(balance(&accounts, &income), Some(-100));
+
+        assert_eq!(
+            balance(&state.accounts_between(None, date(1, 31)), &bank),
+            Some(0)
+        );
+        assert_eq!(
+            balance(&state.accounts_between(Some(date(2, 2)), date(3, 1)), &bank),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn accounts_between_drops_old_reversals_from_the_day_they_were_made() {
+        let (mut state, bank, _, entry_id) = earned();
+        state.apply(
+            JournalEvent::ReversedEntry {
+                entry_id,
+                reason: String::new(),
+                offset: None,
+            },
+            at(3, 1),
+        );
+
+        assert_eq!(
+            balance(&state.accounts_between(None, date(2, 28)), &bank),
+            Some(100)
+        );
+        assert_eq!(
+            balance(&state.accounts_between(None, date(3, 1)), &bank),
+            Some(0)
+        );
+        // a period that only holds the reversal shows it taking the amount back out
+        assert_eq!(
+            balance(
+                &state.accounts_between(Some(date(3, 1)), date(3, 31)),
+                &bank
+            ),
+            Some(-100)
+        );
+        assert_eq!(
+            balance(
+                &state.accounts_between(Some(date(2, 1)), date(3, 31)),
+                &bank
+            ),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn accounts_between_counts_offsetting_entries_by_their_date() {
+        let (mut state, bank, income, entry_id) = earned();
+        let offset = Uuid::new_v4();
+        state.apply(
+            JournalEvent::ReversedEntry {
+                entry_id,
+                reason: String::new(),
+                offset: Some(offset),
+            },
+            at(3, 1),
+        );
+        state.apply(entry(offset, date(3, 1), income, bank, 100), at(3, 1));
+
+        assert_eq!(
+            balance(&state.accounts_between(None, date(2, 28)), &bank),
+            Some(100)
+        );
+        assert_eq!(
+            balance(&state.accounts_between(None, date(3, 1)), &bank),
+            Some(0)
+        );
+        assert_eq!(balance(&state.accounts, &bank), Some(0));
+    }
+
+    #[test]
+    fn normal_balance_flips_credit_normal_types() {
+        // 250 of debits more than credits
+        let expected = [
+            (AccountType::Asset, 250),
+            (AccountType::Liability, -250),
+            (AccountType::Equity, -250),
+            (AccountType::Income, -250),
+            (AccountType::Expense, 250),
+        ];
+        for (account_type, normal) in expected {
+            assert_eq!(
+                account_type.normal_balance(250),
+                normal,
+                "{}",
+                account_type.name()
+            );
+            assert_eq!(
+                account_type.normal_balance(-250),
+                -normal,
+                "{}",
+                account_type.name()
+            );
+        }
+        assert_eq!(AccountType::Income.normal_balance(i64::MIN), i64::MAX);
+    }
+
+    /// a parent account with a child, and a grandchild under that
+    fn family() -> (JournalState, Uuid, Uuid, Uuid) {
+        let (parent, child, grandchild) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut state = JournalState::default();
+        for id in [parent, child, grandchild] {
+            state.apply(account(id, AccountType::Expense), at(1, 1));
+        }
+        for (account_id, parent) in [(child, parent), (grandchild, child)] {
+            state.apply(
+                JournalEvent::MovedAccount {
+                    account_id,
+                    parent: Some(parent),
+                },
+                at(1, 1),
+            );
+        }
+        (state, parent, child, grandchild)
+    }
+
+    #[test]
+    fn can_parent_accepts_a_matching_account() {
+        let (mut state, parent, _, grandchild) = family();
+        let sibling = Uuid::new_v4();
+        state.apply(account(sibling, AccountType::Expense), at(1, 1));
+
+        assert!(state.can_parent(&sibling, &parent));
+        assert!(state.can_parent(&sibling, &grandchild));
+        assert!(state.can_parent(&grandchild, &parent));
+    }
+
+    #[test]
+    fn can_parent_rejects_cycles() {
+        let (state, parent, child, grandchild) = family();
+
+        assert!(!state.can_parent(&parent, &parent));
+        assert!(!state.can_parent(&parent, &child));
+        assert!(!state.can_parent(&parent, &grandchild));
+        assert!(!state.can_parent(&child, &grandchild));
+    }
+
+    #[test]
+    fn can_parent_rejects_a_different_type_or_currency() {
+        let (mut state, parent, _, _) = family();
+        let (asset, euros) = (Uuid::new_v4(), Uuid::new_v4());
+        state.apply(account(asset, AccountType::Asset), at(1, 1));
+        state.apply(
+            JournalEvent::CreatedAccount {
+                id: euros,
+                account_name: "Euros".to_string(),
+                currency: Currency::Eur,
+                account_type: AccountType::Expense,
+            },
+            at(1, 1),
+        );
+
+        assert!(!state.can_parent(&asset, &parent));
+        assert!(!state.can_parent(&parent, &asset));
+        assert!(!state.can_parent(&euros, &parent));
+        assert!(!state.can_parent(&parent, &euros));
+    }
+
+    #[test]
+    fn can_parent_rejects_archived_and_unknown_parents() {
+        let (mut state, parent, _, _) = family();
+        let archived = Uuid::new_v4();
+        state.apply(account(archived, AccountType::Expense), at(1, 1));
+        state.apply(
+            JournalEvent::ArchivedAccount {
+                account_id: archived,
+            },
+            at(1, 1),
+        );
+
+        assert!(!state.can_parent(&parent, &archived));
+        assert!(!state.can_parent(&parent, &Uuid::new_v4()));
+        assert!(!state.can_parent(&Uuid::new_v4(), &parent));
+    }
+}
@@ -0,0 +1,834 @@
+//! frozen payload layouts of `JournalEvent`. each one only knows how to upcast into the
+//! layout that replaced it, so when the enum changes, freeze it as a new module here and
+//! point the previous newest one at it instead of at the current enum
+
+use super::StoredEvent;
+use postcard::from_bytes;
+
+pub fn from_v0(event: &StoredEvent) -> Result<super::JournalEvent, postcard::Error> {
+    let v0 = from_bytes::<v0::JournalEvent>(event.payload)?;
+    Ok(v0
+        .upcast(event)
+        .upcast()
+        .upcast()
+        .upcast(event)
+        .upcast(event)
+        .upcast()
+        .upcast())
+}
+
+pub fn from_v1(event: &StoredEvent) -> Result<super::JournalEvent, postcard::Error> {
+    let v1 = from_bytes::<v1::JournalEvent>(event.payload)?;
+    Ok(v1
+        .upcast()
+        .upcast()
+        .upcast(event)
+        .upcast(event)
+        .upcast()
+        .upcast())
+}
+
+pub fn from_v2(event: &StoredEvent) -> Result<super::JournalEvent, postcard::Error> {
+    let v2 = from_bytes::<v2::JournalEvent>(event.payload)?;
+    Ok(v2.upcast().upcast(event).upcast(event).upcast().upcast())
+}
+
+pub fn from_v3(event: &StoredEvent) -> Result<super::JournalEvent, postcard::Error> {
+    let v3 = from_bytes::<v3::JournalEvent>(event.payload)?;
+    Ok(v3.upcast(event).upcast(event).upcast().upcast())
+}
+
+pub fn from_v4(event: &StoredEvent) -> Result<super::JournalEvent, postcard::Error> {
+    let v4 = from_bytes::<v4::JournalEvent>(event.payload)?;
+    Ok(v4.upcast(event).upcast().upcast())
+}
+
+pub fn from_v5(event: &StoredEvent) -> Result<super::JournalEvent, postcard::Error> {
+    let v5 = from_bytes::<v5::JournalEvent>(event.payload)?;
+    Ok(v5.upcast().upcast())
+}
+
+pub fn from_v6(event: &StoredEvent) -> Result<super::JournalEvent, postcard::Error> {
+    let v6 = from_bytes::<v6::JournalEvent>(event.payload)?;
+    Ok(v6.upcast())
+}
+
+/// the layout before `CreatedAccount` carried its account id
+mod v0 {
+    use super::StoredEvent;
+    use super::v2::Transaction;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    pub enum JournalEvent {
+        Created { name: String, owner: Uuid },
+        Renamed { name: String },
+        CreatedAccount { account_name: String },
+        DeletedAccount { account_id: Uuid },
+        AddedEntry { transaction: Transaction },
+        Deleted,
+    }
+
+    impl JournalEvent {
+        pub fn upcast(self, event: &StoredEvent) -> super::v1::JournalEvent {
+            use super::v1::JournalEvent as Next;
+            match self {
+                Self::Created { name, owner } => Next::Created { name, owner },
+                Self::Renamed { name } => Next::Renamed { name },
+                // the id has to be derived from the row, otherwise it would change on every replay
+                Self::CreatedAccount { account_name } => Next::CreatedAccount {
+                    id: Uuid::from_u64_pair(0, event.id as u64),
+                    account_name,
+                },
+                Self::DeletedAccount { account_id } => Next::DeletedAccount { account_id },
+                Self::AddedEntry { transaction } => Next::AddedEntry { transaction },
+                Self::Deleted => Next::Deleted,
+            }
+        }
+    }
+}
+
+/// the layout before accounts had a currency, they were all kept in dollars
+mod v1 {
+    use super::v2::Transaction;
+    use crate::event_sourcing::currency::Currency;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    pub enum JournalEvent {
+        Created { name: String, owner: Uuid },
+        Renamed { name: String },
+        CreatedAccount { id: Uuid, account_name: String },
+        DeletedAccount { account_id: Uuid },
+        AddedEntry { transaction: Transaction },
+        Deleted,
+    }
+
+    impl JournalEvent {
+        pub fn upcast(self) -> super::v2::JournalEvent {
+            use super::v2::JournalEvent as Next;
+            match self {
+                Self::Created { name, owner } => Next::Created { name, owner },
+                Self::Renamed { name } => Next::Renamed { name },
+                Self::CreatedAccount { id, account_name } => Next::CreatedAccount {
+                    id,
+                    account_name,
+                    currency: Currency::Usd,
+                },
+                Self::DeletedAccount { account_id } => Next::DeletedAccount { account_id },
+                Self::AddedEntry { transaction } => Next::AddedEntry { transaction },
+                Self::Deleted => Next::Deleted,
+            }
+        }
+    }
+}
+
+/// the layout before entries recorded the exchange rates they were converted with
+mod v2 {
+    use super::v3 as next;
+    use crate::event_sourcing::currency::Currency;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    pub struct BalanceUpdate {
+        pub account_id: Uuid,
+        pub changed_by: i64,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Transaction {
+        pub author: Uuid,
+        pub updates: Vec<BalanceUpdate>,
+    }
+
+    #[derive(Deserialize)]
+    pub enum JournalEvent {
+        Created {
+            name: String,
+            owner: Uuid,
+        },
+        Renamed {
+            name: String,
+        },
+        CreatedAccount {
+            id: Uuid,
+            account_name: String,
+            currency: Currency,
+        },
+        DeletedAccount {
+            account_id: Uuid,
+        },
+        AddedEntry {
+            transaction: Transaction,
+        },
+        Deleted,
+    }
+
+    impl JournalEvent {
+        pub fn upcast(self) -> next::JournalEvent {
+            use next::JournalEvent as Next;
+            match self {
+                Self::Created { name, owner } => Next::Created { name, owner },
+                Self::Renamed { name } => Next::Renamed { name },
+                Self::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                } => Next::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                },
+                Self::DeletedAccount { account_id } => Next::DeletedAccount { account_id },
+                Self::AddedEntry { transaction } => Next::AddedEntry {
+                    transaction: next::Transaction {
+                        author: transaction.author,
+                        updates: transaction
+                            .updates
+                            .into_iter()
+                            .map(|update| next::BalanceUpdate {
+                                account_id: update.account_id,
+                                changed_by: update.changed_by,
+                            })
+                            .collect(),
+                        exchange_rates: Vec::new(),
+                    },
+                },
+                Self::Deleted => Next::Deleted,
+            }
+        }
+    }
+}
+
+/// the layout before entries had a description, memos and an effective date
+mod v3 {
+    use super::StoredEvent;
+    use super::v4 as next;
+    use crate::event_sourcing::currency::{Currency, ExchangeRate};
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    pub struct BalanceUpdate {
+        pub account_id: Uuid,
+        pub changed_by: i64,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Transaction {
+        pub author: Uuid,
+        pub updates: Vec<BalanceUpdate>,
+        pub exchange_rates: Vec<ExchangeRate>,
+    }
+
+    #[derive(Deserialize)]
+    pub enum JournalEvent {
+        Created {
+            name: String,
+            owner: Uuid,
+        },
+        Renamed {
+            name: String,
+        },
+        CreatedAccount {
+            id: Uuid,
+            account_name: String,
+            currency: Currency,
+        },
+        DeletedAccount {
+            account_id: Uuid,
+        },
+        AddedEntry {
+            transaction: Transaction,
+        },
+        Deleted,
+        RecordedExchangeRate {
+            rate: ExchangeRate,
+        },
+        CreatedFxAccount {
+            id: Uuid,
+            currency: Currency,
+        },
+    }
+
+    impl JournalEvent {
+        pub fn upcast(self, event: &StoredEvent) -> next::JournalEvent {
+            use next::JournalEvent as Next;
+            match self {
+                Self::Created { name, owner } => Next::Created { name, owner },
+                Self::Renamed { name } => Next::Renamed { name },
+                Self::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                } => Next::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                },
+                Self::DeletedAccount { account_id } => Next::DeletedAccount { account_id },
+                // entries were only ever posted on the day they happened
+                Self::AddedEntry { transaction } => Next::AddedEntry {
+                    transaction: next::Transaction {
+                        author: transaction.author,
+                        description: String::new(),
+                        effective_date: event.created_at.date_naive(),
+                        updates: transaction
+                            .updates
+                            .into_iter()
+                            .map(|update| next::BalanceUpdate {
+                                account_id: update.account_id,
+                                changed_by: update.changed_by,
+                                memo: None,
+                            })
+                            .collect(),
+                        exchange_rates: transaction.exchange_rates,
+                    },
+                },
+                Self::Deleted => Next::Deleted,
+                Self::RecordedExchangeRate { rate } => Next::RecordedExchangeRate { rate },
+                Self::CreatedFxAccount { id, currency } => Next::CreatedFxAccount { id, currency },
+            }
+        }
+    }
+}
+
+/// the layout before entries had ids and could be reversed
+mod v4 {
+    use super::StoredEvent;
+    use super::v5 as next;
+    use crate::event_sourcing::currency::{Currency, ExchangeRate};
+    use chrono::NaiveDate;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    pub struct BalanceUpdate {
+        pub account_id: Uuid,
+        pub changed_by: i64,
+        pub memo: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Transaction {
+        pub author: Uuid,
+        pub description: String,
+        pub effective_date: NaiveDate,
+        pub updates: Vec<BalanceUpdate>,
+        pub exchange_rates: Vec<ExchangeRate>,
+    }
+
+    #[derive(Deserialize)]
+    pub enum JournalEvent {
+        Created {
+            name: String,
+            owner: Uuid,
+        },
+        Renamed {
+            name: String,
+        },
+        CreatedAccount {
+            id: Uuid,
+            account_name: String,
+            currency: Currency,
+        },
+        DeletedAccount {
+            account_id: Uuid,
+        },
+        AddedEntry {
+            transaction: Transaction,
+        },
+        Deleted,
+        RecordedExchangeRate {
+            rate: ExchangeRate,
+        },
+        CreatedFxAccount {
+            id: Uuid,
+            currency: Currency,
+        },
+    }
+
+    impl JournalEvent {
+        pub fn upcast(self, event: &StoredEvent) -> next::JournalEvent {
+            use next::JournalEvent as Next;
+            match self {
+                Self::Created { name, owner } => Next::Created { name, owner },
+                Self::Renamed { name } => Next::Renamed { name },
+                Self::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                } => Next::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                },
+                Self::DeletedAccount { account_id } => Next::DeletedAccount { account_id },
+                // derived from the row for the same reason as the account ids in v0
+                Self::AddedEntry { transaction } => Next::AddedEntry {
+                    transaction: next::Transaction {
+                        id: Uuid::from_u64_pair(0, event.id as u64),
+                        author: transaction.author,
+                        description: transaction.description,
+                        effective_date: transaction.effective_date,
+                        updates: transaction
+                            .updates
+                            .into_iter()
+                            .map(|update| next::BalanceUpdate {
+                                account_id: update.account_id,
+                                changed_by: update.changed_by,
+                                memo: update.memo,
+                            })
+                            .collect(),
+                        exchange_rates: transaction.exchange_rates,
+                        corrects: None,
+                    },
+                },
+                Self::Deleted => Next::Deleted,
+                Self::RecordedExchangeRate { rate } => Next::RecordedExchangeRate { rate },
+                Self::CreatedFxAccount { id, currency } => Next::CreatedFxAccount { id, currency },
+            }
+        }
+    }
+}
+
+/// the layout before accounts had a type
+mod v5 {
+    use super::v6 as next;
+    use crate::event_sourcing::currency::{Currency, ExchangeRate};
+    use crate::event_sourcing::journal as current;
+    use chrono::NaiveDate;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    pub struct BalanceUpdate {
+        pub account_id: Uuid,
+        pub changed_by: i64,
+        pub memo: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Transaction {
+        pub id: Uuid,
+        pub author: Uuid,
+        pub description: String,
+        pub effective_date: NaiveDate,
+        pub updates: Vec<BalanceUpdate>,
+        pub exchange_rates: Vec<ExchangeRate>,
+        pub corrects: Option<Uuid>,
+    }
+
+    #[derive(Deserialize)]
+    pub enum JournalEvent {
+        Created {
+            name: String,
+            owner: Uuid,
+        },
+        Renamed {
+            name: String,
+        },
+        CreatedAccount {
+            id: Uuid,
+            account_name: String,
+            currency: Currency,
+        },
+        DeletedAccount {
+            account_id: Uuid,
+        },
+        AddedEntry {
+            transaction: Transaction,
+        },
+        Deleted,
+        RecordedExchangeRate {
+            rate: ExchangeRate,
+        },
+        CreatedFxAccount {
+            id: Uuid,
+            currency: Currency,
+        },
+        ReversedEntry {
+            entry_id: Uuid,
+            reason: String,
+        },
+        Restored,
+        RenamedAccount {
+            account_id: Uuid,
+            name: String,
+        },
+        ArchivedAccount {
+            account_id: Uuid,
+        },
+    }
+
+    impl JournalEvent {
+        pub fn upcast(self) -> next::JournalEvent {
+            use next::JournalEvent as Next;
+            match self {
+                Self::Created { name, owner } => Next::Created { name, owner },
+                Self::Renamed { name } => Next::Renamed { name },
+                // untyped accounts were shown as debit balances, so they become assets
+                Self::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                } => Next::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                    account_type: current::AccountType::Asset,
+                },
+                Self::DeletedAccount { account_id } => Next::DeletedAccount { account_id },
+                Self::AddedEntry { transaction } => Next::AddedEntry {
+                    transaction: next::Transaction {
+                        id: transaction.id,
+                        author: transaction.author,
+                        description: transaction.description,
+                        effective_date: transaction.effective_date,
+                        updates: transaction
+                            .updates
+                            .into_iter()
+                            .map(|update| next::BalanceUpdate {
+                                account_id: update.account_id,
+                                changed_by: update.changed_by,
+                                memo: update.memo,
+                            })
+                            .collect(),
+                        exchange_rates: transaction.exchange_rates,
+                        corrects: transaction.corrects,
+                    },
+                },
+                Self::Deleted => Next::Deleted,
+                Self::RecordedExchangeRate { rate } => Next::RecordedExchangeRate { rate },
+                Self::CreatedFxAccount { id, currency } => Next::CreatedFxAccount { id, currency },
+                Self::ReversedEntry { entry_id, reason } => {
+                    Next::ReversedEntry { entry_id, reason }
+                }
+                Self::Restored => Next::Restored,
+                Self::RenamedAccount { account_id, name } => {
+                    Next::RenamedAccount { account_id, name }
+                }
+                Self::ArchivedAccount { account_id } => Next::ArchivedAccount { account_id },
+            }
+        }
+    }
+}
+
+/// the layout before reversals posted an offsetting entry
+mod v6 {
+    use crate::event_sourcing::currency::{Currency, ExchangeRate};
+    use crate::event_sourcing::journal as current;
+    use chrono::NaiveDate;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    pub struct BalanceUpdate {
+        pub account_id: Uuid,
+        pub changed_by: i64,
+        pub memo: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Transaction {
+        pub id: Uuid,
+        pub author: Uuid,
+        pub description: String,
+        pub effective_date: NaiveDate,
+        pub updates: Vec<BalanceUpdate>,
+        pub exchange_rates: Vec<ExchangeRate>,
+        pub corrects: Option<Uuid>,
+    }
+
+    #[derive(Deserialize)]
+    pub enum JournalEvent {
+        Created {
+            name: String,
+            owner: Uuid,
+        },
+        Renamed {
+            name: String,
+        },
+        CreatedAccount {
+            id: Uuid,
+            account_name: String,
+            currency: Currency,
+            account_type: current::AccountType,
+        },
+        DeletedAccount {
+            account_id: Uuid,
+        },
+        AddedEntry {
+            transaction: Transaction,
+        },
+        Deleted,
+        RecordedExchangeRate {
+            rate: ExchangeRate,
+        },
+        CreatedFxAccount {
+            id: Uuid,
+            currency: Currency,
+        },
+        ReversedEntry {
+            entry_id: Uuid,
+            reason: String,
+        },
+        Restored,
+        RenamedAccount {
+            account_id: Uuid,
+            name: String,
+        },
+        ArchivedAccount {
+            account_id: Uuid,
+        },
+        MovedAccount {
+            account_id: Uuid,
+            parent: Option<Uuid>,
+        },
+    }
+
+    impl JournalEvent {
+        pub fn upcast(self) -> current::JournalEvent {
+            use current::JournalEvent as Next;
+            match self {
+                Self::Created { name, owner } => Next::Created { name, owner },
+                Self::Renamed { name } => Next::Renamed { name },
+                Self::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                    account_type,
+                } => Next::CreatedAccount {
+                    id,
+                    account_name,
+                    currency,
+                    account_type,
+                },
+                Self::DeletedAccount { account_id } => Next::DeletedAccount { account_id },
+                Self::AddedEntry { transaction } => Next::AddedEntry {
+                    transaction: current::Transaction {
+                        id: transaction.id,
+                        author: transaction.author,
+                        description: transaction.description,
+                        effective_date: transaction.effective_date,
+                        updates: transaction
+                            .updates
+                            .into_iter()
+                            .map(|update| current::BalanceUpdate {
+                                account_id: update.account_id,
+                                changed_by: update.changed_by,
+                                memo: update.memo,
+                            })
+                            .collect(),
+                        exchange_rates: transaction.exchange_rates,
+                        corrects: transaction.corrects,
+                    },
+                },
+                Self::Deleted => Next::Deleted,
+                Self::RecordedExchangeRate { rate } => Next::RecordedExchangeRate { rate },
+                Self::CreatedFxAccount { id, currency } => Next::CreatedFxAccount { id, currency },
+                // these were offset in the balances directly, there's no entry to point at
+                Self::ReversedEntry { entry_id, reason } => Next::ReversedEntry {
+                    entry_id,
+                    reason,
+                    offset: None,
+                },
+                Self::Restored => Next::Restored,
+                Self::RenamedAccount { account_id, name } => {
+                    Next::RenamedAccount { account_id, name }
+                }
+                Self::ArchivedAccount { account_id } => Next::ArchivedAccount { account_id },
+                Self::MovedAccount { account_id, parent } => {
+                    Next::MovedAccount { account_id, parent }
+                }
+            }
+        }
+    }
+}
+
+/// one payload per frozen layout, as it was stored, decoded all the way to the current enum
+#[cfg(test)]
+mod tests {
+    use super::StoredEvent;
+    use crate::event_sourcing::currency::{Currency, ExchangeRate};
+    use crate::event_sourcing::journal::{AccountType, BalanceUpdate, JournalEvent};
+    use chrono::{DateTime, NaiveDate, Utc};
+    use uuid::Uuid;
+
+    const ROW_ID: i64 = 9;
+    const AUTHOR: Uuid = Uuid::from_u128(0x26825e85_347c_44c0_976c_ef0412beef21);
+    const BANK: Uuid = Uuid::from_u128(0xf5be0958_6c40_410a_a7de_8aef830c231d);
+    const CASH: Uuid = Uuid::from_u128(0x8bca9349_c9ba_4d10_96db_126ef1cc5954);
+    const FX: Uuid = Uuid::from_u128(0x99253024_941b_44cb_952b_55fe1d8ea371);
+
+    fn recorded_at() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_792_209_406, 0).unwrap_or_default()
+    }
+
+    fn decode(schema_version: i16, hex: &str) -> JournalEvent {
+        let payload: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| match u8::from_str_radix(&hex[i..i + 2], 16) {
+                Ok(byte) => byte,
+                Err(e) => panic!("bad fixture: {e}"),
+            })
+            .collect();
+        let event = StoredEvent {
+            id: ROW_ID,
+            schema_version,
+            payload: &payload,
+            created_at: recorded_at(),
+        };
+        match JournalEvent::decode(&event) {
+            Ok(event) => event,
+            Err(e) => panic!("version {schema_version} didn't decode: {e}"),
+        }
+    }
+
+    fn update(account_id: Uuid, changed_by: i64, memo: Option<&str>) -> BalanceUpdate {
+        BalanceUpdate {
+            account_id,
+            changed_by,
+            memo: memo.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn v0_account_gets_its_id_from_the_row() {
+        let JournalEvent::CreatedAccount {
+            id,
+            account_name,
+            currency,
+            account_type,
+        } = decode(0, "020442616e6b")
+        else {
+            panic!("expected an account");
+        };
+
+        assert_eq!(id, Uuid::from_u64_pair(0, ROW_ID as u64));
+        assert_eq!(account_name, "Bank");
+        assert_eq!(currency, Currency::Usd);
+        assert_eq!(account_type, AccountType::Asset);
+    }
+
+    #[test]
+    fn v1_entry_is_dated_the_day_it_was_recorded() {
+        let JournalEvent::AddedEntry { transaction } = decode(
+            1,
+            "041026825e85347c44c0976cef0412beef210210f5be09586c40410aa7de8aef830c231dc4131099253024941b44cb952b55fe1d8ea371c313",
+        ) else {
+            panic!("expected an entry");
+        };
+
+        assert_eq!(transaction.id, Uuid::from_u64_pair(0, ROW_ID as u64));
+        assert_eq!(transaction.author, AUTHOR);
+        assert_eq!(transaction.description, "");
+        assert_eq!(transaction.effective_date, recorded_at().date_naive());
+        assert_eq!(
+            transaction.updates,
+            vec![update(BANK, 1250, None), update(FX, -1250, None)]
+        );
+        assert!(transaction.exchange_rates.is_empty());
+        assert_eq!(transaction.corrects, None);
+    }
+
+    #[test]
+    fn v2_account_keeps_its_currency() {
+        let JournalEvent::CreatedAccount {
+            id,
+            account_name,
+            currency,
+            account_type,
+        } = decode(2, "02108bca9349c9ba4d1096db126ef1cc5954044361736801")
+        else {
+            panic!("expected an account");
+        };
+
+        assert_eq!(id, CASH);
+        assert_eq!(account_name, "Cash");
+        assert_eq!(currency, Currency::Eur);
+        assert_eq!(account_type, AccountType::Asset);
+    }
+
+    #[test]
+    fn v3_entry_keeps_its_exchange_rates() {
+        let JournalEvent::AddedEntry { transaction } = decode(
+            3,
+            "041026825e85347c44c0976cef0412beef210310f5be09586c40410aa7de8aef830c231df0ab01108bca9349c9ba4d1096db126ef1cc59549f9c011099253024941b44cb952b55fe1d8ea37163010100c0d9b668",
+        ) else {
+            panic!("expected an entry");
+        };
+
+        assert_eq!(transaction.id, Uuid::from_u64_pair(0, ROW_ID as u64));
+        assert_eq!(transaction.effective_date, recorded_at().date_naive());
+        assert_eq!(
+            transaction.updates,
+            vec![
+                update(BANK, 11000, None),
+                update(CASH, -10000, None),
+                update(FX, -50, None),
+            ]
+        );
+        assert_eq!(
+            transaction.exchange_rates,
+            vec![ExchangeRate {
+                base: Currency::Eur,
+                quote: Currency::Usd,
+                rate: 109_500_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn v4_entry_keeps_its_description_date_and_memos() {
+        let JournalEvent::AddedEntry { transaction } = decode(
+            4,
+            "041026825e85347c44c0976cef0412beef2106436f666665650a323032362d30312d30320210f5be09586c40410aa7de8aef830c231dc80101036375701099253024941b44cb952b55fe1d8ea371c7010000",
+        ) else {
+            panic!("expected an entry");
+        };
+
+        assert_eq!(transaction.id, Uuid::from_u64_pair(0, ROW_ID as u64));
+        assert_eq!(transaction.author, AUTHOR);
+        assert_eq!(transaction.description, "Coffee");
+        assert_eq!(
+            transaction.effective_date,
+            NaiveDate::from_ymd_opt(2026, 1, 2).unwrap_or_default()
+        );
+        assert_eq!(
+            transaction.updates,
+            vec![update(BANK, 100, Some("cup")), update(FX, -100, None)]
+        );
+        assert_eq!(transaction.corrects, None);
+    }
+
+    #[test]
+    fn v5_account_becomes_an_asset() {
+        let JournalEvent::CreatedAccount { account_type, .. } =
+            decode(5, "0210f5be09586c40410aa7de8aef830c231d0442616e6b00")
+        else {
+            panic!("expected an account");
+        };
+
+        assert_eq!(account_type, AccountType::Asset);
+    }
+
+    #[test]
+    fn v6_reversal_has_no_offsetting_entry() {
+        let JournalEvent::ReversedEntry {
+            entry_id,
+            reason,
+            offset,
+        } = decode(6, "08100000000000000000000000000000000a046f6f7073")
+        else {
+            panic!("expected a reversal");
+        };
+
+        assert_eq!(entry_id, Uuid::from_u64_pair(0, 10));
+        assert_eq!(reason, "oops");
+        assert_eq!(offset, None);
+    }
+}
@@ -9,3 +9,18 @@ pub mod journal;
 
 #[allow(dead_code)]
 pub mod username;
+
+#[allow(dead_code)]
+pub mod snapshot;
+
+#[allow(dead_code)]
+pub mod stream;
+
+#[allow(dead_code)]
+pub mod upcast;
+
+#[allow(dead_code)]
+pub mod unit_of_work;
+
+#[allow(dead_code)]
+pub mod currency;
@@ -0,0 +1,104 @@
+use leptos::prelude::ServerFnError;
+use postcard::{from_bytes, to_allocvec};
+use serde::{Serialize, de::DeserializeOwned};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// how many events have to be replayed on top of a snapshot before a new one is written
+pub const SNAPSHOT_INTERVAL: usize = 100;
+
+#[derive(sqlx::Type, Clone, Copy)]
+#[sqlx(type_name = "smallint")]
+#[repr(i16)]
+pub enum AggregateType {
+    User = 1,
+    Journal = 2,
+}
+
+pub struct Snapshot<T> {
+    pub last_sequence: i64,
+    pub aggregate: T,
+}
+
+/// aggregates can be built from a subset of their event types,
+/// so each distinct subset gets its own snapshot
+pub fn event_filter(event_types: impl IntoIterator<Item = i16>) -> i64 {
+    event_types
+        .into_iter()
+        .fold(0, |filter, event_type| filter | 1 << event_type)
+}
+
+pub async fn load<T: DeserializeOwned>(
+    aggregate_id: &Uuid,
+    aggregate_type: AggregateType,
+    event_filter: i64,
+    schema_version: i16,
+    pool: &PgPool,
+) -> Result<Option<Snapshot<T>>, ServerFnError> {
+    let row = sqlx::query_as::<_, (i64, Vec<u8>)>(
+        r#"
+        SELECT last_sequence, payload FROM snapshots
+        WHERE aggregate_id = $1
+            AND aggregate_type = $2
+            AND event_filter = $3
+            AND schema_version = $4
+        "#,
+    )
+    .bind(aggregate_id)
+    .bind(aggregate_type)
+    .bind(event_filter)
+    .bind(schema_version)
+    .fetch_optional(pool)
+    .await?;
+
+    match row {
+        Some((last_sequence, payload)) => Ok(Some(Snapshot {
+            last_sequence,
+            aggregate: from_bytes(&payload)?,
+        })),
+        None => Ok(None),
+    }
+}
+
+/// overwrites any existing snapshot for the same aggregate and filter,
+/// including ones written with an older schema version
+pub async fn save<T: Serialize>(
+    aggregate_id: &Uuid,
+    aggregate_type: AggregateType,
+    event_filter: i64,
+    schema_version: i16,
+    aggregate: &T,
+    last_sequence: i64,
+    pool: &PgPool,
+) -> Result<(), ServerFnError> {
+    let payload: Vec<u8> = to_allocvec(aggregate)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO snapshots (
+            aggregate_id,
+            aggregate_type,
+            event_filter,
+            schema_version,
+            last_sequence,
+            payload
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (aggregate_id, aggregate_type, event_filter) DO UPDATE SET
+            schema_version = EXCLUDED.schema_version,
+            last_sequence = EXCLUDED.last_sequence,
+            payload = EXCLUDED.payload,
+            created_at = now()
+        "#,
+    )
+    .bind(aggregate_id)
+    .bind(aggregate_type)
+    .bind(event_filter)
+    .bind(schema_version)
+    .bind(last_sequence)
+    .bind(payload)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
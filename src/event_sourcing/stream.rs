@@ -0,0 +1,124 @@
+use chrono::Utc;
+use leptos::prelude::ServerFnError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::upcast::StoredEvent;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stream {
+    User,
+    Journal,
+}
+
+impl Stream {
+    fn table(self) -> &'static str {
+        match self {
+            Self::User => "user_events",
+            Self::Journal => "journal_events",
+        }
+    }
+
+    fn aggregate_column(self) -> &'static str {
+        match self {
+            Self::User => "user_id",
+            Self::Journal => "journal_id",
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub struct EventRow {
+    pub id: i64,
+    pub sequence: i64,
+    pub schema_version: i16,
+    pub payload: Vec<u8>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+impl EventRow {
+    pub fn stored(&self) -> StoredEvent<'_> {
+        StoredEvent {
+            id: self.id,
+            schema_version: self.schema_version,
+            payload: &self.payload,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// reads an aggregate's events of the given types in the order they were appended.
+/// sequences are unique per aggregate, so replaying them is deterministic
+/// even when several events share a timestamp
+pub async fn read(
+    stream: Stream,
+    aggregate_id: &Uuid,
+    event_types: &[i16],
+    after_sequence: i64,
+    pool: &PgPool,
+) -> Result<Vec<EventRow>, ServerFnError> {
+    read_until(
+        stream,
+        aggregate_id,
+        event_types,
+        after_sequence,
+        i64::MAX,
+        pool,
+    )
+    .await
+}
+
+/// like `read`, but stops at `up_to_sequence`, for replaying the aggregate as it was then
+pub async fn read_until(
+    stream: Stream,
+    aggregate_id: &Uuid,
+    event_types: &[i16],
+    after_sequence: i64,
+    up_to_sequence: i64,
+    pool: &PgPool,
+) -> Result<Vec<EventRow>, ServerFnError> {
+    let rows = sqlx::query_as::<_, EventRow>(&format!(
+        r#"
+        SELECT id, sequence, schema_version, payload, created_at FROM {}
+        WHERE {} = $1 AND event_type = ANY($2) AND sequence > $3 AND sequence <= $4
+        ORDER BY sequence ASC
+        "#,
+        stream.table(),
+        stream.aggregate_column()
+    ))
+    .bind(aggregate_id)
+    .bind(event_types)
+    .bind(after_sequence)
+    .bind(up_to_sequence)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// the sequence number of the latest event in the stream, or 0 if it has none
+pub async fn current_sequence(
+    stream: Stream,
+    aggregate_id: &Uuid,
+    pool: &PgPool,
+) -> Result<i64, ServerFnError> {
+    let sequence: Option<i64> = sqlx::query_scalar(&format!(
+        r#"
+        SELECT MAX(sequence) FROM {}
+        WHERE {} = $1
+        "#,
+        stream.table(),
+        stream.aggregate_column()
+    ))
+    .bind(aggregate_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(sequence.unwrap_or(0))
+}
+
+/// an append lost the race for its `(aggregate_id, sequence)` slot
+/// because another event was written to the stream after it was read
+pub fn is_conflict(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(e) if e.is_unique_violation())
+}
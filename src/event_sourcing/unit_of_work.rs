@@ -0,0 +1,138 @@
+use leptos::prelude::ServerFnError;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::auth::AuthEvent;
+use super::journal::JournalEvent;
+use super::stream::Stream;
+use super::user::UserEvent;
+use super::username;
+
+enum Append {
+    User {
+        id: Uuid,
+        expected_sequence: i64,
+        event: UserEvent,
+    },
+    Journal {
+        id: Uuid,
+        expected_sequence: i64,
+        event: JournalEvent,
+    },
+    Username {
+        user_id: Uuid,
+        username: String,
+    },
+    Auth {
+        user_id: Uuid,
+        session_id: String,
+        event: AuthEvent,
+    },
+}
+
+/// collects events for any number of streams and appends them in a single postgres
+/// transaction, so a command either writes all of its events or none of them
+#[derive(Default)]
+pub struct UnitOfWork {
+    appends: Vec<Append>,
+    next_sequences: HashMap<(Stream, Uuid), i64>,
+}
+
+impl UnitOfWork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `expected_sequence` is the version the stream was read at, later
+    /// events for the same stream in this unit are sequenced after it
+    fn next_sequence(&mut self, stream: Stream, id: Uuid, expected_sequence: i64) -> i64 {
+        let next = self
+            .next_sequences
+            .entry((stream, id))
+            .or_insert(expected_sequence);
+        let sequence = *next;
+        *next += 1;
+        sequence
+    }
+
+    pub fn push_user(&mut self, id: Uuid, expected_sequence: i64, event: UserEvent) -> &mut Self {
+        let expected_sequence = self.next_sequence(Stream::User, id, expected_sequence);
+        self.appends.push(Append::User {
+            id,
+            expected_sequence,
+            event,
+        });
+        self
+    }
+
+    pub fn push_journal(
+        &mut self,
+        id: Uuid,
+        expected_sequence: i64,
+        event: JournalEvent,
+    ) -> &mut Self {
+        let expected_sequence = self.next_sequence(Stream::Journal, id, expected_sequence);
+        self.appends.push(Append::Journal {
+            id,
+            expected_sequence,
+            event,
+        });
+        self
+    }
+
+    pub fn push_username(&mut self, user_id: Uuid, username: String) -> &mut Self {
+        self.appends.push(Append::Username { user_id, username });
+        self
+    }
+
+    pub fn push_auth(&mut self, user_id: Uuid, session_id: String, event: AuthEvent) -> &mut Self {
+        self.appends.push(Append::Auth {
+            user_id,
+            session_id,
+            event,
+        });
+        self
+    }
+
+    /// the journal events queued so far, in the order they'll be appended
+    #[cfg(test)]
+    pub fn journal_events(&self) -> impl Iterator<Item = &JournalEvent> {
+        self.appends.iter().filter_map(|append| match append {
+            Append::Journal { event, .. } => Some(event),
+            _ => None,
+        })
+    }
+
+    /// if any append fails the transaction is rolled back when it's dropped
+    pub async fn commit(self, pool: &PgPool) -> Result<(), ServerFnError> {
+        let mut tx = pool.begin().await?;
+
+        for append in self.appends {
+            match append {
+                Append::User {
+                    id,
+                    expected_sequence,
+                    event,
+                } => _ = event.push_db(&id, expected_sequence, &mut *tx).await?,
+                Append::Journal {
+                    id,
+                    expected_sequence,
+                    event,
+                } => _ = event.push_db(&id, expected_sequence, &mut *tx).await?,
+                Append::Username { user_id, username } => {
+                    _ = username::update(&user_id, &username, &mut *tx).await?
+                }
+                Append::Auth {
+                    user_id,
+                    session_id,
+                    event,
+                } => _ = event.push_db(&user_id, &session_id, &mut *tx).await?,
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use chrono::Utc;
+use leptos::prelude::ServerFnError;
+use postcard::from_bytes;
+use serde::de::DeserializeOwned;
+
+/// a raw row from one of the event tables
+pub struct StoredEvent<'a> {
+    pub id: i64,
+    pub schema_version: i16,
+    pub payload: &'a [u8],
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+/// decodes a payload written with an older layout straight into the current event enum
+pub type Upcaster<E> = fn(&StoredEvent) -> Result<E, postcard::Error>;
+
+/// postcard isn't self-describing, so every stored payload is tagged with the layout
+/// version it was written with. when an event enum changes, freeze a copy of the old
+/// layout, bump `current` and register an upcaster for the old version here
+pub struct UpcasterRegistry<E: 'static> {
+    pub current: i16,
+    pub upcasters: &'static [(i16, Upcaster<E>)],
+}
+
+impl<E: DeserializeOwned> UpcasterRegistry<E> {
+    pub fn decode(&self, event: &StoredEvent) -> Result<E, ServerFnError> {
+        if event.schema_version == self.current {
+            return Ok(from_bytes(event.payload)?);
+        }
+
+        match self
+            .upcasters
+            .iter()
+            .find(|(version, _)| *version == event.schema_version)
+        {
+            Some((_, upcast)) => Ok(upcast(event)?),
+            None => Err(ServerFnError::ServerError(format!(
+                "no upcaster registered for version {} of event {}",
+                event.schema_version, event.id
+            ))),
+        }
+    }
+}
@@ -1,13 +1,20 @@
 use super::journal::JournalTenantInfo;
 use leptos::prelude::ServerFnError;
-use postcard::{from_bytes, to_allocvec};
+use postcard::to_allocvec;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, query_as};
+use sqlx::{PgExecutor, PgPool};
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use super::snapshot::{self, AggregateType, SNAPSHOT_INTERVAL};
+use super::stream::{self, Stream};
+use super::upcast::{StoredEvent, UpcasterRegistry};
+use crate::api::return_types::KnownErrors;
 use crate::event_sourcing::journal::Permissions;
 
+/// bump this whenever the layout of `UserState` changes so old snapshots are ignored
+const SNAPSHOT_VERSION: i16 = 1;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum UserEvent {
     Created {
@@ -38,9 +45,18 @@ pub enum UserEvent {
         id: Uuid,
     },
     Deleted,
+    TenantPermissionsUpdated {
+        id: Uuid,
+        permissions: Permissions,
+    },
 }
 
-#[derive(sqlx::Type)]
+const EVENT_REGISTRY: UpcasterRegistry<UserEvent> = UpcasterRegistry {
+    current: 1,
+    upcasters: &[],
+};
+
+#[derive(sqlx::Type, Clone, Copy)]
 #[sqlx(type_name = "smallint")]
 #[repr(i16)]
 pub enum UserEventType {
@@ -54,6 +70,7 @@ pub enum UserEventType {
     RemovedFromJournal = 8,
     SelectedJournal = 9,
     Deleted = 10,
+    TenantPermissionsUpdated = 11,
 }
 
 impl UserEvent {
@@ -69,34 +86,70 @@ impl UserEvent {
             Self::RemovedFromJournal { .. } => RemovedFromJournal,
             Self::SelectedJournal { .. } => SelectedJournal,
             Self::Deleted => Deleted,
+            Self::TenantPermissionsUpdated { .. } => TenantPermissionsUpdated,
         }
     }
-    pub async fn push_db(&self, uuid: &Uuid, pool: &PgPool) -> Result<i64, ServerFnError> {
+    /// the journal the event is about, stored in its own column so tenants can be looked up by journal
+    pub fn journal_id(&self) -> Option<Uuid> {
+        match self {
+            Self::CreatedJournal { id }
+            | Self::InvitedToJournal { id, .. }
+            | Self::AcceptedJournalInvite { id }
+            | Self::DeclinedJournalInvite { id }
+            | Self::RemovedFromJournal { id }
+            | Self::SelectedJournal { id }
+            | Self::TenantPermissionsUpdated { id, .. } => Some(*id),
+            Self::Created { .. } | Self::PasswordUpdated { .. } | Self::Deleted => None,
+        }
+    }
+
+    pub fn decode(event: &StoredEvent) -> Result<Self, ServerFnError> {
+        EVENT_REGISTRY.decode(event)
+    }
+
+    pub async fn push_db<'e>(
+        &self,
+        uuid: &Uuid,
+        expected_sequence: i64,
+        executor: impl PgExecutor<'e>,
+    ) -> Result<i64, ServerFnError> {
         let event_type = self.get_type();
         let payload: Vec<u8> = to_allocvec(self)?;
 
-        let id: i64 = sqlx::query_scalar(
+        let id: Result<i64, sqlx::Error> = sqlx::query_scalar(
             r#"
             INSERT INTO user_events (
                 user_id,
+                sequence,
                 event_type,
-                payload
+                schema_version,
+                payload,
+                journal_id
             )
-            VALUES ($1, $2, $3)
+            VALUES ($1, $2, $3, $4, $5, $6)
             RETURNING id
             "#,
         )
         .bind(uuid)
+        .bind(expected_sequence + 1)
         .bind(event_type)
+        .bind(EVENT_REGISTRY.current)
         .bind(payload)
-        .fetch_one(pool)
-        .await?;
+        .bind(self.journal_id())
+        .fetch_one(executor)
+        .await;
 
-        Ok(id)
+        match id {
+            Ok(id) => Ok(id),
+            Err(e) if stream::is_conflict(&e) => Err(ServerFnError::ServerError(
+                KnownErrors::ConcurrencyConflict.to_string()?,
+            )),
+            Err(e) => Err(e.into()),
+        }
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct UserState {
     pub id: Uuid,
     pub authenticated_sessions: std::collections::HashSet<String>,
@@ -104,8 +157,12 @@ pub struct UserState {
     pub pending_journal_invites: HashMap<Uuid, JournalTenantInfo>,
     pub accepted_journal_invites: HashMap<Uuid, JournalTenantInfo>,
     pub owned_journals: HashSet<Uuid>,
+    /// the journal `/` opens, only a ui preference. server functions always take the journal id
     pub selected_journal: Uuid,
     pub deleted: bool,
+    /// the stream version this state was read at, pass it to `push_db` as the expected sequence
+    #[serde(skip)]
+    pub sequence: i64,
 }
 
 impl UserState {
@@ -114,30 +171,54 @@ impl UserState {
         event_types: Vec<UserEventType>,
         pool: &PgPool,
     ) -> Result<Self, ServerFnError> {
-        let user_events = query_as::<_, (Vec<u8>,)>(
-            r#"
-            SELECT payload FROM user_events
-            WHERE user_id = $1 AND event_type = ANY($2)
-            ORDER BY created_at ASC
-            "#,
+        let event_types: Vec<i16> = event_types.into_iter().map(|t| t as i16).collect();
+        let event_filter = snapshot::event_filter(event_types.iter().copied());
+
+        let (mut aggregate, mut last_sequence) = match snapshot::load::<Self>(
+            id,
+            AggregateType::User,
+            event_filter,
+            SNAPSHOT_VERSION,
+            pool,
         )
-        .bind(id)
-        .bind(&event_types)
-        .fetch_all(pool)
-        .await?;
-
-        let mut aggregate = Self {
-            id: *id,
-            selected_journal: Uuid::nil(),
-            ..Default::default()
+        .await?
+        {
+            Some(s) => (s.aggregate, s.last_sequence),
+            None => (
+                Self {
+                    id: *id,
+                    selected_journal: Uuid::nil(),
+                    ..Default::default()
+                },
+                0,
+            ),
         };
 
-        user_events
-            .into_iter()
-            .try_for_each(|(payload,)| -> Result<(), ServerFnError> {
-                aggregate.apply(from_bytes::<UserEvent>(&payload)?);
-                Ok(())
-            })?;
+        // read before the events so a concurrent append can only cause a spurious conflict
+        let sequence = current_sequence(id, pool).await?;
+
+        let user_events = stream::read(Stream::User, id, &event_types, last_sequence, pool).await?;
+
+        let replayed = user_events.len();
+        aggregate.sequence = sequence;
+
+        for event in user_events {
+            aggregate.apply(UserEvent::decode(&event.stored())?);
+            last_sequence = event.sequence;
+        }
+
+        if replayed >= SNAPSHOT_INTERVAL {
+            snapshot::save(
+                id,
+                AggregateType::User,
+                event_filter,
+                SNAPSHOT_VERSION,
+                &aggregate,
+                last_sequence,
+                pool,
+            )
+            .await?;
+        }
 
         Ok(aggregate)
     }
@@ -181,6 +262,11 @@ impl UserState {
             UserEvent::RemovedFromJournal { id } => _ = self.accepted_journal_invites.remove(&id),
             UserEvent::SelectedJournal { id } => self.selected_journal = id,
             UserEvent::Deleted => self.deleted = true,
+            UserEvent::TenantPermissionsUpdated { id, permissions } => {
+                if let Some(tenant_info) = self.accepted_journal_invites.get_mut(&id) {
+                    tenant_info.tenant_permissions = permissions;
+                }
+            }
         }
     }
 }
@@ -195,3 +281,113 @@ pub async fn get_hashed_pw(user_id: &Uuid, pool: &PgPool) -> Result<String, Serv
 
     Ok(user.hashed_password)
 }
+
+/// everyone who has accepted an invite to the journal and not been removed since.
+/// tenancy is only recorded on the tenants' own streams, so this replays the
+/// invite events of every user
+pub async fn get_journal_tenants(
+    journal_id: &Uuid,
+    pool: &PgPool,
+) -> Result<HashMap<Uuid, JournalTenantInfo>, ServerFnError> {
+    use UserEventType::*;
+
+    let event_types: Vec<i16> = [
+        InvitedToJournal,
+        AcceptedJournalInvite,
+        DeclinedJournalInvite,
+        RemovedFromJournal,
+        TenantPermissionsUpdated,
+    ]
+    .into_iter()
+    .map(|t| t as i16)
+    .collect();
+
+    let rows = sqlx::query_as::<_, (Uuid, i64, i16, Vec<u8>, chrono::DateTime<chrono::Utc>)>(
+        r#"
+        SELECT user_id, id, schema_version, payload, created_at FROM user_events
+        WHERE journal_id = $1 AND event_type = ANY($2)
+        ORDER BY user_id, sequence
+        "#,
+    )
+    .bind(journal_id)
+    .bind(&event_types)
+    .fetch_all(pool)
+    .await?;
+
+    let mut users: HashMap<Uuid, UserState> = HashMap::new();
+
+    for (user_id, id, schema_version, payload, created_at) in rows {
+        let event = UserEvent::decode(&StoredEvent {
+            id,
+            schema_version,
+            payload: &payload,
+            created_at,
+        })?;
+        users.entry(user_id).or_default().apply(event);
+    }
+
+    Ok(users
+        .into_iter()
+        .filter_map(|(user_id, state)| {
+            state
+                .accepted_journal_invites
+                .get(journal_id)
+                .map(|tenant_info| (user_id, tenant_info.clone()))
+        })
+        .collect())
+}
+
+/// the sequence number of the latest event in the user's stream, or 0 if it has none
+pub async fn current_sequence(id: &Uuid, pool: &PgPool) -> Result<i64, ServerFnError> {
+    stream::current_sequence(Stream::User, id, pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the migration backfilling `journal_id` reads the id straight out of the payload
+    #[test]
+    fn journal_id_follows_the_variant_tag_and_length_prefix() {
+        let id = Uuid::new_v4();
+        let events = [
+            UserEvent::CreatedJournal { id },
+            UserEvent::InvitedToJournal {
+                id,
+                permissions: Permissions::READ,
+                inviting_user: Uuid::new_v4(),
+                owner: Uuid::new_v4(),
+            },
+            UserEvent::AcceptedJournalInvite { id },
+            UserEvent::DeclinedJournalInvite { id },
+            UserEvent::RemovedFromJournal { id },
+            UserEvent::SelectedJournal { id },
+            UserEvent::TenantPermissionsUpdated {
+                id,
+                permissions: Permissions::all(),
+            },
+        ];
+
+        for event in events {
+            let payload = match to_allocvec(&event) {
+                Ok(payload) => payload,
+                Err(e) => panic!("{event:?} didn't encode: {e}"),
+            };
+            assert_eq!(event.journal_id(), Some(id));
+            assert_eq!(payload.get(1), Some(&16));
+            assert_eq!(payload.get(2..18), Some(id.as_bytes().as_slice()));
+        }
+    }
+
+    #[test]
+    fn other_events_have_no_journal() {
+        assert_eq!(UserEvent::Deleted.journal_id(), None);
+        assert_eq!(
+            UserEvent::PasswordUpdated {
+                hashed_password: String::new()
+            }
+            .journal_id(),
+            None
+        );
+    }
+}
@@ -1,11 +1,11 @@
 use leptos::prelude::ServerFnError;
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 
-pub async fn update(
+pub async fn update<'e>(
     user_id: &Uuid,
     username: &String,
-    pool: &PgPool,
+    executor: impl PgExecutor<'e>,
 ) -> Result<i64, ServerFnError> {
     let id: i64 = sqlx::query_scalar(
         r#"
@@ -19,7 +19,7 @@ pub async fn update(
     )
     .bind(user_id)
     .bind(username)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     Ok(id)
@@ -30,7 +30,7 @@ pub async fn get_username(user_id: &Uuid, pool: &PgPool) -> Result<Option<String
         r#"
         SELECT username FROM username_events
         WHERE user_id = $1
-        ORDER BY created_at DESC
+        ORDER BY id DESC
         LIMIT 1
         "#,
     )
@@ -46,7 +46,7 @@ pub async fn get_id(username: &String, pool: &PgPool) -> Result<Option<Uuid>, Se
         r#"
         SELECT user_id FROM username_events
         WHERE username = $1
-        ORDER BY created_at DESC
+        ORDER BY id DESC
         LIMIT 1
         "#,
     )
@@ -37,56 +37,10 @@ async fn main() {
         .await
         .expect("failed to connect to the postgres pool");
 
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS user_events (
-            id BIGSERIAL PRIMARY KEY,
-            user_id UUID NOT NULL,
-            event_type SMALLINT NOT NULL,
-            payload BYTEA NOT NULL,
-            created_at TIMESTAMPTZ NOT NULL DEFAULT now()
-            )",
-    )
-    .execute(&pool)
-    .await
-    .expect("failed to create the user events table");
-
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS journal_events (
-            id BIGSERIAL PRIMARY KEY,
-            journal_id UUID NOT NULL,
-            event_type SMALLINT NOT NULL,
-            payload BYTEA NOT NULL,
-            created_at TIMESTAMPTZ NOT NULL DEFAULT now()
-            )",
-    )
-    .execute(&pool)
-    .await
-    .expect("failed to create the journal events table");
-
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS auth_events (
-            id BIGSERIAL PRIMARY KEY,
-            user_id UUID NOT NULL,
-            session_id BYTEA NOT NULL,
-            event_type SMALLINT NOT NULL,
-            created_at TIMESTAMPTZ NOT NULL DEFAULT now()
-            )",
-    )
-    .execute(&pool)
-    .await
-    .expect("failed to create the auth events table");
-
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS username_events (
-                id BIGSERIAL PRIMARY KEY,
-                user_id UUID NOT NULL,
-                username VARCHAR(64) NOT NULL,
-                created_at TIMESTAMPTZ NOT NULL DEFAULT now()
-                )",
-    )
-    .execute(&pool)
-    .await
-    .expect("failed to create the auth events table");
+    sqlx::migrate!()
+        .run(&pool)
+        .await
+        .expect("failed to run the database migrations");
 
     let session_store = PostgresStore::new(pool.clone());
     session_store
@@ -94,6 +48,12 @@ async fn main() {
         .await
         .expect("failed to migrate the session store");
 
+    // `monkesto migrate` applies pending migrations without starting the server
+    if env::args().nth(1).is_some_and(|arg| arg == "migrate") {
+        log!("migrations applied");
+        return;
+    }
+
     let session_layer = SessionManagerLayer::new(session_store)
         .with_expiry(Expiry::OnInactivity(Duration::hours(48)));
 
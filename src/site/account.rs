@@ -1,257 +1,737 @@
+use super::handle_error::{ActionError, HandleError};
 use super::layout::Layout;
 use crate::api::main_api;
-use crate::api::return_types::*;
-use crate::event_sourcing::journal::Permissions;
+use crate::api::return_types::{Account, KnownErrors};
+use crate::event_sourcing::currency::Currency;
+use crate::event_sourcing::journal::{AccountType, Permissions};
+use chrono::{NaiveDate, TimeZone};
 use leptos::prelude::*;
-
 use uuid::Uuid;
 
-#[component]
-fn AddAccount(user_id: Uuid, journal_id: Uuid) -> impl IntoView {
-    let add_account = ServerAction::<main_api::AddAccount>::new();
-    view! {
-        <div class="flex flex-col items-center text-center px-10 py-10">
-            <h1>"Create a new account"</h1>
-            <ActionForm action=add_account>
-                <input
-                    class="shadow appearance-none border rounded py-2 px-2 text-gray-700 leading-tight focus:outline-none focus:shadow-outline"
-                    type="text"
-                    name="account_name"
-                    required
-                />
-
-                <input type="hidden" name="user_id" value=user_id.to_string() />
-                <input type="hidden" name="journal_id" value=journal_id.to_string() />
-
-                <button
-                    class="mt-3 rounded bg-purple-900 px-2 py-2 font-bold text-white hover:bg-blue-400"
-                    type="submit"
-                >
-                    "Add"
-                </button>
-            </ActionForm>
-
-            {move || {
-                match add_account.value().get() {
-                    Some(Err(e)) => {
-
-                        view! {
-                            <p>"An error occurred while creating the account: " {e.to_string()}</p>
-                        }
-                            .into_any()
-                    }
-                    _ => view! { "" }.into_any(),
-                }
-            }}
-
-        </div>
-    }
+/// the forms on each account card, grouped so the tree can pass them down
+#[derive(Clone, Copy)]
+struct AccountActions {
+    rename: ServerAction<main_api::RenameAccount>,
+    archive: ServerAction<main_api::ArchiveAccount>,
+    delete: ServerAction<main_api::DeleteAccount>,
+    move_account: ServerAction<main_api::MoveAccount>,
 }
 
+/// renaming, moving, archiving and deleting. the server refuses to delete accounts with
+/// a balance or entries, those can only be archived
 #[component]
-pub fn AccountList(mut accounts: Vec<Account>, journals: Journals, user_id: Uuid) -> impl IntoView {
-    let create_journal = ServerAction::<main_api::CreateJournal>::new();
+fn AccountControls(
+    journal_id: String,
+    account: Account,
+    /// every active account, the ones that can hold this account are offered as parents
+    accounts: Vec<Account>,
+    actions: AccountActions,
+) -> impl IntoView {
+    let move_journal_id = journal_id.clone();
+    let archive_journal_id = journal_id.clone();
+    let delete_journal_id = journal_id.clone();
+    let account_id = account.id;
+    // an account can't be moved under itself or one of its own sub-accounts
+    let descendants: Vec<Uuid> = Account::flatten(account.children.clone())
+        .into_iter()
+        .map(|child| child.id)
+        .collect();
+    let parents: Vec<Account> = accounts
+        .into_iter()
+        .filter(|parent| {
+            parent.id != account.id
+                && !descendants.contains(&parent.id)
+                && parent.account_type == account.account_type
+                && parent.currency == account.currency
+        })
+        .collect();
 
     view! {
-        <div class="mx-auto flex min-w-full flex-col items-center px-4 py-4">
-            <ActionForm action=create_journal>
-                <input type="hidden" name="user_id" value=user_id.to_string() />
+        <div class="mt-3 flex flex-wrap items-center gap-3">
+            <ActionForm action=actions.rename attr:class="flex items-center gap-2">
+                <input type="hidden" name="journal_id" value=journal_id />
+                <input type="hidden" name="account_id" value=account_id.to_string() />
                 <input
-                    class="shadow appearance-none border rounded py-2 px-2 text-gray-700 leading-tight focus:outline-none focus:shadow-outline"
-                    name="journal_name"
                     type="text"
-                    placeholder="journal name"
+                    name="name"
+                    value=account.name
                     required
+                    class="rounded-md bg-white px-2 py-1 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10"
                 />
                 <button
                     type="submit"
-                    class="mt-3 rounded bg-purple-900 px-2 py-2 font-bold text-white hover:bg-blue-400"
+                    class="text-sm font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300"
                 >
-                    "Create Journal!"
+                    "Rename"
                 </button>
             </ActionForm>
-
-            {move || {
-                match create_journal.value().get() {
-                    Some(Err(e)) => {
-                        view! {
-                            <p>"An error occurred while creating the journal: " {e.to_string()}</p>
-                        }
-                            .into_any()
-                    }
-                    _ => {
-
-                        view! { "" }
-                            .into_any()
-                    }
-                }
-            }}
-
-            <h1 class="font-bold text-4xl">"Accounts"</h1>
-        </div>
-        <div class="mx-auto flex min-w-full flex-col items-center">
-            <ul>
-                {
-                    accounts.sort_unstable_by_key(|account| account.name.clone());
-                    accounts
+            <ActionForm action=actions.move_account attr:class="flex items-center gap-2">
+                <input type="hidden" name="journal_id" value=move_journal_id />
+                <input type="hidden" name="account_id" value=account_id.to_string() />
+                <select
+                    name="parent_id"
+                    class="rounded-md bg-white px-2 py-1 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10"
+                >
+                    <option value="" selected=account.parent.is_none()>
+                        "Top level"
+                    </option>
+                    {parents
                         .into_iter()
-                        .map(|account| {
+                        .map(|parent| {
+                            let selected = account.parent == Some(parent.id);
                             view! {
-                                <li class="px-1 py-1 font-bold text-2xl">
-                                    {account.name}"    "
-                                    {format!(
-                                        "${}.{:02} {}",
-                                        account.balance.abs() / 100,
-                                        account.balance.abs() % 100,
-                                        if account.balance < 0 { "Dr" } else { "Cr" },
-                                    )}
-                                </li>
+                                <option value=parent.id.to_string() selected=selected>
+                                    {parent.name}
+                                </option>
                             }
                         })
-                        .collect_view()
-                }
-            </ul>
+                        .collect_view()}
+                </select>
+                <button
+                    type="submit"
+                    class="text-sm font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300"
+                >
+                    "Move"
+                </button>
+            </ActionForm>
+            <ActionForm action=actions.archive>
+                <input type="hidden" name="journal_id" value=archive_journal_id />
+                <input type="hidden" name="account_id" value=account_id.to_string() />
+                <button
+                    type="submit"
+                    class="text-sm font-semibold text-gray-600 hover:text-gray-500 dark:text-gray-400 dark:hover:text-gray-300"
+                >
+                    "Archive"
+                </button>
+            </ActionForm>
+            <ActionForm action=actions.delete>
+                <input type="hidden" name="journal_id" value=delete_journal_id />
+                <input type="hidden" name="account_id" value=account_id.to_string() />
+                <button
+                    type="submit"
+                    class="text-sm font-semibold text-red-600 hover:text-red-500 dark:text-red-400 dark:hover:text-red-300"
+                >
+                    "Delete"
+                </button>
+            </ActionForm>
         </div>
-
-        {move || {
-            if let Some(selected) = journals.selected.clone() {
-                match selected {
-                    AssociatedJournal::Owned { id, .. } => {
-                        view! { <AddAccount user_id=user_id journal_id=id /> }.into_any()
-                    }
-                    AssociatedJournal::Shared { id, tenant_info, .. } => {
-                        if tenant_info.tenant_permissions.contains(Permissions::ADDACCOUNT) {
-                            view! { <AddAccount user_id=user_id journal_id=id /> }.into_any()
-                        } else {
-                            view! { "" }.into_any()
-                        }
-                    }
-                }
-            } else {
-                view! { "" }.into_any()
-            }
-        }}
     }
 }
 
-struct AccountItem {
-    pub id: Uuid,
-    pub name: String,
-    pub balance: i64, // in cents
-}
+/// an active account and its sub-accounts, with balances shown on their normal side so a
+/// liability that is owed shows as positive. parents show their rolled up total and can
+/// be collapsed
+fn account_tree(
+    journal_id: String,
+    account: Account,
+    accounts: Vec<Account>,
+    actions: Option<AccountActions>,
+) -> AnyView {
+    let href = format!("/journal/{}/account/{}", journal_id, account.id);
+    let normal = |balance| account.account_type.normal_balance(balance);
+    let total = account.currency.format(normal(account.total_balance));
+    let own = (!account.children.is_empty() && account.balance != 0).then(|| {
+        format!(
+            "{} in this account",
+            account.currency.format(normal(account.balance))
+        )
+    });
+    let name = account.name.clone();
+    let children: Vec<Account> = account
+        .children
+        .iter()
+        .filter(|child| !child.archived)
+        .cloned()
+        .collect();
+    let nested = children
+        .into_iter()
+        .map(|child| account_tree(journal_id.clone(), child, accounts.clone(), actions))
+        .collect_view();
+    let controls = actions.map(|actions| {
+        view! {
+            <AccountControls
+                journal_id=journal_id
+                account=account.clone()
+                accounts=accounts
+                actions=actions
+            />
+        }
+    });
 
-fn accounts() -> Vec<AccountItem> {
-    use std::str::FromStr;
-    vec![
-        AccountItem {
-            id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440000").expect("Invalid UUID"),
-            name: "Cash".to_string(),
-            balance: 25043, // $250.43
-        },
-        AccountItem {
-            id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440001").expect("Invalid UUID"),
-            name: "Checking Account".to_string(),
-            balance: 152067, // $1,520.67
-        },
-        AccountItem {
-            id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440002").expect("Invalid UUID"),
-            name: "Savings Account".to_string(),
-            balance: 500000, // $5,000.00
-        },
-    ]
+    let card = view! {
+        <div class="flex justify-between items-center">
+            <a
+                href=href
+                class="text-lg font-semibold text-gray-900 dark:text-white hover:opacity-80"
+            >
+                {name}
+            </a>
+            <div class="text-right">
+                <div class="text-lg font-medium text-gray-900 dark:text-white">{total}</div>
+                <div class="text-xs text-gray-500 dark:text-gray-400">{own}</div>
+            </div>
+        </div>
+        {controls}
+    };
+
+    if account.children.iter().all(|child| child.archived) {
+        view! {
+            <div class="mt-2 p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl">
+                {card}
+            </div>
+        }
+        .into_any()
+    } else {
+        view! {
+            <details open class="mt-2">
+                <summary class="list-none cursor-pointer p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl">
+                    {card}
+                </summary>
+                <div class="ml-6">{nested}</div>
+            </details>
+        }
+        .into_any()
+    }
 }
 
-fn journals() -> Vec<super::journal::Journal> {
-    use std::str::FromStr;
-    vec![
-        super::journal::Journal {
-            id: Uuid::from_str("550e8400-e29b-41d4-a716-446655440000").expect("Invalid UUID"),
-            name: "Personal".to_string(),
-            creator_username: "johndoe".to_string(),
-            created_at: "2024-01-15 09:30:45".to_string(),
-        },
-        super::journal::Journal {
-            id: Uuid::from_str("550e8400-e29b-41d4-a716-446655440001").expect("Invalid UUID"),
-            name: "Business".to_string(),
-            creator_username: "janesmith".to_string(),
-            created_at: "2024-01-20 14:22:18".to_string(),
-        },
-    ]
+/// midnight at the start of the day after `date` in the zone the app shows times in, the
+/// first moment that isn't part of `date`. `None` past the last date chrono can represent
+fn end_of_day(date: NaiveDate) -> Option<chrono::DateTime<chrono::Utc>> {
+    date.succ_opt()
+        .map(|next| next.and_time(chrono::NaiveTime::MIN))
+        .and_then(|midnight| {
+            chrono_tz::America::Chicago
+                .from_local_datetime(&midnight)
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&chrono::Utc))
 }
 
 #[component]
 pub fn AccountListPage() -> impl IntoView {
-    use leptos_router::hooks::use_params_map;
+    use leptos_router::hooks::{use_params_map, use_query_map};
 
     let params = use_params_map();
     let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
+    let query = use_query_map();
+    // set when looking at the balances as they stood at the end of a past day
+    let as_of = move || {
+        query
+            .get()
+            .get("as_of")
+            .and_then(|date| date.parse::<NaiveDate>().ok())
+    };
 
-    let journal_name = move || {
-        journals()
-            .into_iter()
-            .find(|j| j.id.to_string() == journal_id())
-            .map(|j| j.name)
-            .unwrap_or_else(|| "Unknown Journal".to_string())
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    let accounts_resource = Resource::new(
+        move || (journal_id(), as_of()),
+        |(journal_id, as_of)| async move {
+            let journal_id = Uuid::try_parse(&journal_id)?;
+            match as_of {
+                None => main_api::get_accounts(journal_id).await,
+                Some(date) => {
+                    let Some(end) = end_of_day(date) else {
+                        return Err(ServerFnError::ServerError(
+                            KnownErrors::InvalidInput.to_string()?,
+                        ));
+                    };
+                    main_api::get_accounts_as_of(journal_id, end).await
+                }
+            }
+        },
+    );
+
+    let add_account = ServerAction::<main_api::AddAccount>::new();
+    let actions = AccountActions {
+        rename: ServerAction::new(),
+        archive: ServerAction::new(),
+        delete: ServerAction::new(),
+        move_account: ServerAction::new(),
     };
 
     view! {
-        <Layout page_title=journal_name() show_switch_link=true journal_id=journal_id()>
-            {accounts()
-                .into_iter()
-                .map(|account| {
-                    view! {
-                        <a
-                            href=format!("/journal/{}/account/{}", journal_id(), account.id)
-                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"
-                        >
-                            <div class="flex justify-between items-center">
-                                <h3 class="text-lg font-semibold text-gray-900 dark:text-white">
-                                    {account.name}
-                                </h3>
-                                <div class="text-right">
-                                    <div class="text-lg font-medium text-gray-900 dark:text-white">
-                                        {format!(
-                                            "${}.{:02}",
-                                            account.balance / 100,
-                                            account.balance % 100,
-                                        )}
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+                let tree = match accounts_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching accounts").into_any(),
+                };
+                let (archived, active): (Vec<Account>, Vec<Account>) = Account::flatten(
+                        tree.clone(),
+                    )
+                    .into_iter()
+                    .partition(|account| account.archived);
+                let has_archived = !archived.is_empty();
+                let historical = as_of();
+                let actions = (historical.is_none()
+                    && journal.has_permission(Permissions::ADDACCOUNT))
+                    .then_some(actions);
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        <form method="get" class="flex flex-wrap items-end gap-3">
+                            <label class="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                                "Balances as of "
+                                <input
+                                    type="date"
+                                    name="as_of"
+                                    value=historical
+                                        .unwrap_or_else(|| chrono::Utc::now().date_naive())
+                                        .to_string()
+                                    class="rounded-md bg-white px-3 py-1.5 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10"
+                                />
+                            </label>
+                            <button
+                                type="submit"
+                                class="rounded-md bg-indigo-600 px-3 py-1.5 text-sm font-semibold text-white shadow-xs hover:bg-indigo-500 dark:bg-indigo-500 dark:hover:bg-indigo-400"
+                            >
+                                "Show"
+                            </button>
+                        </form>
+                        {historical
+                            .map(|date| {
+                                view! {
+                                    <div class="mt-4 p-3 flex justify-between items-center rounded-lg bg-amber-50 text-sm text-amber-800 dark:bg-amber-900/30 dark:text-amber-200">
+                                        <span>
+                                            {format!(
+                                                "Showing the accounts as they stood at the end of {}",
+                                                date,
+                                            )}
+                                        </span>
+                                        <a
+                                            href=format!("/journal/{}/account", journal_id())
+                                            class="font-semibold hover:opacity-80"
+                                        >
+                                            "Back to today"
+                                        </a>
+                                    </div>
+                                }
+                            })}
+                        // every card's forms share these actions, and a form posted without
+                        // javascript doesn't say which card it came from, so refusals are
+                        // shown once above the tree
+                        {actions
+                            .map(|actions| {
+                                view! {
+                                    {ActionError(actions.rename, "renaming the account")}
+                                    {ActionError(actions.move_account, "moving the account")}
+                                    {ActionError(actions.archive, "archiving the account")}
+                                    {ActionError(actions.delete, "deleting the account")}
+                                }
+                            })}
+                        {AccountType::ALL
+                            .into_iter()
+                            .filter_map(|account_type| {
+                                let group: Vec<Account> = tree
+                                    .iter()
+                                    .filter(|account| {
+                                        account.account_type == account_type && !account.archived
+                                    })
+                                    .cloned()
+                                    .collect();
+                                if group.is_empty() {
+                                    return None;
+                                }
+                                Some(
+                                    view! {
+                                        <div class="mt-6 mb-2 flex justify-between items-baseline">
+                                            <h2 class="text-base font-semibold text-gray-900 dark:text-white">
+                                                {account_type.plural()}
+                                            </h2>
+                                            <span class="text-xs text-gray-500 dark:text-gray-400">
+                                                {if account_type.is_debit_normal() {
+                                                    "Normal balance: Dr"
+                                                } else {
+                                                    "Normal balance: Cr"
+                                                }}
+                                            </span>
+                                        </div>
+                                        {group
+                                            .into_iter()
+                                            .map(|account| {
+                                                account_tree(journal_id(), account, active.clone(), actions)
+                                            })
+                                            .collect_view()}
+                                    },
+                                )
+                            })
+                            .collect_view()}
+                        <Show when=move || has_archived>
+                            <h2 class="mt-8 text-base font-semibold text-gray-900 dark:text-white">
+                                "Archived"
+                            </h2>
+                        </Show>
+                        {archived
+                            .into_iter()
+                            .map(|account| {
+                                view! {
+                                    <a
+                                        href=format!(
+                                            "/journal/{}/account/{}",
+                                            journal_id(),
+                                            account.id,
+                                        )
+                                        class="mt-4 flex justify-between items-center p-4 bg-gray-50 dark:bg-gray-800 rounded-lg text-gray-600 dark:text-gray-400 hover:opacity-80"
+                                    >
+                                        <h3 class="text-lg font-semibold">{account.name}</h3>
+                                        <div class="text-lg">
+                                            {account
+                                                .currency
+                                                .format(
+                                                    account.account_type.normal_balance(account.balance),
+                                                )}
+                                        </div>
+                                    </a>
+                                }
+                            })
+                            .collect_view()}
+                        // the past can't be edited, so historical views get no controls
+                        {historical
+                            .is_none()
+                            .then(|| {
+                                view! {
+                                    <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
+                                    <div class="mt-10">
+                                        <ActionForm action=add_account attr:class="space-y-6">
+                                            <input type="hidden" name="journal_id" value=journal_id() />
+                                            {ActionError(add_account, "creating the account")}
+                                            <div>
+                                                <label
+                                                    for="account_name"
+                                                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                                >
+                                                    "Create New Account"
+                                                </label>
+                                                <div class="mt-2">
+                                                    <input
+                                                        id="account_name"
+                                                        type="text"
+                                                        name="account_name"
+                                                        required
+                                                        class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
+                                                    />
+                                                </div>
+                                            </div>
+                                            <div>
+                                                <label
+                                                    for="currency"
+                                                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                                >
+                                                    "Currency"
+                                                </label>
+                                                <div class="mt-2">
+                                                    <select
+                                                        id="currency"
+                                                        name="currency"
+                                                        class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:focus:outline-indigo-500"
+                                                    >
+                                                        {Currency::ALL
+                                                            .into_iter()
+                                                            .map(|currency| {
+                                                                view! {
+                                                                    <option value=currency.code()>{currency.code()}</option>
+                                                                }
+                                                            })
+                                                            .collect_view()}
+                                                    </select>
+                                                </div>
+                                            </div>
+                                            <div>
+                                                <label
+                                                    for="account_type"
+                                                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                                >
+                                                    "Type"
+                                                </label>
+                                                <div class="mt-2">
+                                                    <select
+                                                        id="account_type"
+                                                        name="account_type"
+                                                        class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:focus:outline-indigo-500"
+                                                    >
+                                                        {AccountType::ALL
+                                                            .into_iter()
+                                                            .map(|account_type| {
+                                                                let name = account_type.name();
+                                                                view! { <option value=name>{name}</option> }
+                                                            })
+                                                            .collect_view()}
+                                                    </select>
+                                                </div>
+                                            </div>
+                                            <div>
+                                                <label
+                                                    for="parent_id"
+                                                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                                >
+                                                    "Parent Account"
+                                                </label>
+                                                <div class="mt-2">
+                                                    <select
+                                                        id="parent_id"
+                                                        name="parent_id"
+                                                        class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:focus:outline-indigo-500"
+                                                    >
+                                                        <option value="">"None"</option>
+                                                        {active
+                                                            .iter()
+                                                            .map(|account| {
+                                                                view! {
+                                                                    <option value=account
+                                                                        .id
+                                                                        .to_string()>
+                                                                        {format!(
+                                                                            "{} ({}, {})",
+                                                                            account.name,
+                                                                            account.account_type.name(),
+                                                                            account.currency.code(),
+                                                                        )}
+                                                                    </option>
+                                                                }
+                                                            })
+                                                            .collect_view()}
+                                                    </select>
+                                                </div>
+                                            </div>
+                                            <div>
+                                                <button
+                                                    type="submit"
+                                                    class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500"
+                                                >
+                                                    "Create Account"
+                                                </button>
+                                            </div>
+                                        </ActionForm>
                                     </div>
-                                </div>
+                                }
+                            })}
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
+    }
+}
+
+/// every entry that touched one account or its sub-accounts, with the rolled up balance
+/// after each, newest first
+#[component]
+pub fn AccountLedgerPage() -> impl IntoView {
+    use leptos_router::hooks::{use_params_map, use_query_map};
+
+    let params = use_params_map();
+    let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
+    let account_id = move || {
+        params
+            .get()
+            .get("account_id")
+            .unwrap_or_default()
+            .to_string()
+    };
+    let query = use_query_map();
+    let page = move || {
+        query
+            .get()
+            .get("page")
+            .and_then(|page| page.parse::<usize>().ok())
+            .unwrap_or_default()
+    };
+
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    let ledger_resource = Resource::new(
+        move || (journal_id(), account_id(), page()),
+        |(journal_id, account_id, page)| async move {
+            main_api::get_account_ledger(
+                Uuid::try_parse(&journal_id)?,
+                Uuid::try_parse(&account_id)?,
+                page,
+            )
+            .await
+        },
+    );
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+                let ledger = match ledger_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching the ledger").into_any(),
+                };
+                let account = ledger.account;
+                let currency = account.currency;
+                let account_type = account.account_type;
+                let page_link = move |page: usize| {
+                    format!("/journal/{}/account/{}?page={}", journal_id(), account_id(), page)
+                };
+                let newer = ledger.page.checked_sub(1).map(page_link);
+                let older = (ledger.page + 1 < ledger.page_count)
+                    .then(|| page_link(ledger.page + 1));
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        <div class="mb-4 flex justify-between items-baseline">
+                            <div>
+                                <h1 class="text-xl font-semibold text-gray-900 dark:text-white">
+                                    {account.name}
+                                </h1>
+                                <p class="text-sm text-gray-500 dark:text-gray-400">
+                                    {account_type.name()} " · " {currency.code()}
+                                    {account.archived.then_some(" · archived")}
+                                </p>
+                            </div>
+                            <div class="text-right text-lg font-medium text-gray-900 dark:text-white">
+                                {currency
+                                    .format(account_type.normal_balance(account.total_balance))}
                             </div>
-                        </a>
-                    }
-                })
-                .collect_view()}
-            <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
-            <div class="mt-10">
-                <form class="space-y-6">
-                    <div>
-                        <label
-                            for="account_name"
-                            class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
-                        >
-                            "Create New Account"
-                        </label>
-                        <div class="mt-2">
-                            <input
-                                id="account_name"
-                                type="text"
-                                name="account_name"
-                                required
-                                class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
-                            />
                         </div>
-                    </div>
-                    <div>
-                        <button
-                            type="submit"
-                            class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500"
-                        >
-                            "Create Account"
-                        </button>
-                    </div>
-                </form>
-            </div>
-        </Layout>
+                        {if ledger.lines.is_empty() {
+                            view! {
+                                <p class="text-sm text-gray-500 dark:text-gray-400">
+                                    "No entries have touched this account"
+                                </p>
+                            }
+                                .into_any()
+                        } else {
+                            view! {
+                                <table class="w-full text-left text-gray-900 dark:text-white">
+                                    <thead class="text-sm text-gray-500 dark:text-gray-400">
+                                        <tr>
+                                            <th class="py-2 font-medium">"Date"</th>
+                                            <th class="py-2 font-medium">"Description"</th>
+                                            <th class="py-2 font-medium">"Accounts"</th>
+                                            <th class="py-2 font-medium">"Author"</th>
+                                            <th class="py-2 font-medium text-right">"Amount"</th>
+                                            <th class="py-2 font-medium text-right">"Balance"</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        {ledger
+                                            .lines
+                                            .into_iter()
+                                            .map(|line| {
+                                                let side = if line.changed_by < 0 { "Cr" } else { "Dr" };
+                                                view! {
+                                                    <tr class=if line.reversed {
+                                                        "border-t border-gray-200 dark:border-gray-700 text-gray-400 dark:text-gray-500"
+                                                    } else {
+                                                        "border-t border-gray-200 dark:border-gray-700"
+                                                    }>
+                                                        <td class="py-1">{line.effective_date.to_string()}</td>
+                                                        <td class="py-1">
+                                                            <a
+                                                                href=format!(
+                                                                    "/journal/{}/transaction/{}",
+                                                                    journal_id(),
+                                                                    line.transaction_id,
+                                                                )
+                                                                class="hover:opacity-80"
+                                                            >
+                                                                {line.description}
+                                                                {line.reversed.then_some(" (reversed)")}
+                                                            </a>
+                                                            {(!line.sub_accounts.is_empty())
+                                                                .then(|| {
+                                                                    view! {
+                                                                        <div class="text-xs text-gray-500 dark:text-gray-400">
+                                                                            "in " {line.sub_accounts.join(", ")}
+                                                                        </div>
+                                                                    }
+                                                                })}
+                                                            {line
+                                                                .memo
+                                                                .map(|memo| {
+                                                                    view! {
+                                                                        <div class="text-xs text-gray-500 dark:text-gray-400">
+                                                                            {memo}
+                                                                        </div>
+                                                                    }
+                                                                })}
+                                                        </td>
+                                                        <td class="py-1 text-sm">
+                                                            {line.counter_accounts.join(", ")}
+                                                        </td>
+                                                        <td class="py-1 text-sm">{line.author}</td>
+                                                        <td class="py-1 text-right">
+                                                            {currency.format(line.changed_by.abs())} " " {side}
+                                                        </td>
+                                                        <td class="py-1 text-right">
+                                                            {currency
+                                                                .format(account_type.normal_balance(line.running_balance))}
+                                                        </td>
+                                                    </tr>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </tbody>
+                                </table>
+                            }
+                                .into_any()
+                        }}
+                        <div class="mt-6 flex justify-between text-sm font-semibold text-indigo-600 dark:text-indigo-400">
+                            <span>
+                                {newer
+                                    .map(|href| {
+                                        view! {
+                                            <a href=href class="hover:opacity-80">
+                                                "Newer"
+                                            </a>
+                                        }
+                                    })}
+                            </span>
+                            <span class="text-gray-500 dark:text-gray-400 font-normal">
+                                {format!("Page {} of {}", ledger.page + 1, ledger.page_count)}
+                            </span>
+                            <span>
+                                {older
+                                    .map(|href| {
+                                        view! {
+                                            <a href=href class="hover:opacity-80">
+                                                "Older"
+                                            </a>
+                                        }
+                                    })}
+                            </span>
+                        </div>
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
     }
 }
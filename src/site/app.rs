@@ -1,9 +1,17 @@
+use super::account::AccountLedgerPage;
 use super::account::AccountListPage;
 use super::auth::ClientLogin;
 use super::auth::ClientSignUp;
+use super::journal::Home;
 use super::journal::JournalDetail;
 use super::journal::JournalList;
 use super::person::PeopleListPage;
+use super::rate::ExchangeRatesPage;
+use super::report::BalanceSheetPage;
+use super::report::IncomeStatementPage;
+use super::report::ReportsPage;
+use super::report::TrialBalancePage;
+use super::transaction::TransactionDetailPage;
 use super::transaction::TransactionListPage;
 use leptos::prelude::*;
 use leptos_meta::MetaTags;
@@ -49,18 +57,25 @@ pub fn App() -> impl IntoView {
                 <Routes fallback=|| "Page not found.".into_view()>
                     <Route path=path!("/login") view=ClientLogin />
                     <Route path=path!("/signup") view=ClientSignUp />
-                    <Route
-                        path=path!("/")
-                        view=move || {
-                            view! { <meta http-equiv="refresh" content="0; url=/journal" /> }
-                                .into_view()
-                        }
-                    />
+                    <Route path=path!("/") view=Home />
                     <Route path=path!("/journal") view=JournalList />
                     <Route path=path!("/journal/:id") view=JournalDetail />
                     <Route path=path!("/journal/:id/transaction") view=TransactionListPage />
+                    <Route
+                        path=path!("/journal/:id/transaction/:transaction_id")
+                        view=TransactionDetailPage
+                    />
                     <Route path=path!("/journal/:id/account") view=AccountListPage />
+                    <Route path=path!("/journal/:id/account/:account_id") view=AccountLedgerPage />
                     <Route path=path!("/journal/:id/person") view=PeopleListPage />
+                    <Route path=path!("/journal/:id/rate") view=ExchangeRatesPage />
+                    <Route path=path!("/journal/:id/reports") view=ReportsPage />
+                    <Route path=path!("/journal/:id/reports/trial-balance") view=TrialBalancePage />
+                    <Route path=path!("/journal/:id/reports/balance-sheet") view=BalanceSheetPage />
+                    <Route
+                        path=path!("/journal/:id/reports/income-statement")
+                        view=IncomeStatementPage
+                    />
                 </Routes>
             </main>
         </Router>
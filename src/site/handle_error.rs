@@ -1,6 +1,7 @@
 use leptos::{
     IntoView,
-    prelude::{CustomAttribute, ElementChild, IntoAny, ServerFnError},
+    prelude::{CustomAttribute, ElementChild, Get, IntoAny, ServerAction, ServerFnError},
+    server_fn::ServerFn,
     view,
 };
 
@@ -11,7 +12,8 @@ pub fn HandleError(err: ServerFnError, context: &str) -> impl IntoView {
     use KnownErrors::*;
     if let Some(e) = KnownErrors::parse_error(&err) {
         match e {
-            NotLoggedIn => {
+            // a visitor without a session can't be logged in either
+            NotLoggedIn | SessionIdNotFound => {
                 view! { <meta http-equiv="refresh" content="0; url=/login" /> }.into_any()
             }
 
@@ -27,3 +29,17 @@ pub fn HandleError(err: ServerFnError, context: &str) -> impl IntoView {
         view! { <p>"An unknown error occurred: " {err.to_string()}</p> }.into_any()
     }
 }
+
+/// the error from the last submission of `action`, if it failed. forms posted without
+/// javascript get it back through the redirect, so this works on server rendered pages too
+#[expect(non_snake_case)]
+pub fn ActionError<S>(action: ServerAction<S>, context: &'static str) -> impl IntoView
+where
+    S: ServerFn<Error = ServerFnError> + Clone + Send + Sync + 'static,
+    S::Output: Clone + Send + Sync + 'static,
+{
+    move || match action.value().get() {
+        Some(Err(e)) => HandleError(e, context).into_any(),
+        _ => view! { "" }.into_any(),
+    }
+}
@@ -1,32 +1,34 @@
-use super::handle_error::HandleError;
+use super::handle_error::{ActionError, HandleError};
 use super::layout::Layout;
 use crate::api::main_api;
+use crate::api::return_types::Journals;
+use crate::event_sourcing::journal::{Permissions, RESTORE_GRACE_DAYS};
 use leptos::prelude::*;
 use uuid::Uuid;
 
-pub struct Journal {
-    pub id: Uuid,
-    pub name: String,
-    pub creator_username: String,
-    pub created_at: String,
-}
+/// sends the visitor to the journal they last chose to open by default, or to the
+/// journal list if they haven't picked one
+#[component]
+pub fn Home() -> impl IntoView {
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
 
-fn journals() -> Vec<Journal> {
-    use std::str::FromStr;
-    vec![
-        Journal {
-            id: Uuid::from_str("550e8400-e29b-41d4-a716-446655440000").expect("Invalid UUID"),
-            name: "Personal".to_string(),
-            creator_username: "johndoe".to_string(),
-            created_at: "2024-01-15 09:30:45".to_string(),
-        },
-        Journal {
-            id: Uuid::from_str("550e8400-e29b-41d4-a716-446655440001").expect("Invalid UUID"),
-            name: "Business".to_string(),
-            creator_username: "janesmith".to_string(),
-            created_at: "2024-01-20 14:22:18".to_string(),
-        },
-    ]
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let url = match journals_resource.await {
+                    Ok(Journals { selected: Some(journal), .. }) => {
+                        format!("/journal/{}", journal.get_id())
+                    }
+                    Ok(_) => "/journal".to_string(),
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                view! { <meta http-equiv="refresh" content=format!("0; url={}", url) /> }.into_any()
+            })}
+        </Suspense>
+    }
 }
 
 #[component]
@@ -36,7 +38,13 @@ pub fn JournalList() -> impl IntoView {
         |_| async move { main_api::get_associated_journals().await },
     );
 
+    let deleted_journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_deleted_journals().await },
+    );
+
     let create_journal = ServerAction::<main_api::CreateJournal>::new();
+    let restore_journal = ServerAction::<main_api::RestoreJournal>::new();
 
     view! {
         <Layout>
@@ -101,10 +109,127 @@ pub fn JournalList() -> impl IntoView {
                     </div>
                 </ActionForm>
             </div>
+
+            // outside the list, so a journal past its grace period still explains why it's gone
+            {ActionError(restore_journal, "restoring the journal")}
+            <Suspense>
+                {move || Suspend::new(async move {
+                    let deleted = match deleted_journals_resource.await {
+                        Ok(s) => s,
+                        Err(e) => return HandleError(e, "fetching deleted journals").into_any(),
+                    };
+                    if deleted.is_empty() {
+                        return ().into_any();
+                    }
+                    view! {
+                        <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
+                        <h2 class="text-base font-semibold text-gray-900 dark:text-white">
+                            "Recently Deleted"
+                        </h2>
+                        {deleted
+                            .into_iter()
+                            .map(|journal| {
+                                view! {
+                                    <div class="mt-4 flex justify-between items-center p-4 bg-gray-50 dark:bg-gray-800 rounded-lg">
+                                        <div>
+                                            <h3 class="text-lg font-semibold text-gray-900 dark:text-white">
+                                                {journal.name}
+                                            </h3>
+                                            <p class="text-sm text-gray-600 dark:text-gray-400">
+                                                "Can be restored until "
+                                                {journal
+                                                    .restorable_until
+                                                    .with_timezone(&chrono_tz::America::Chicago)
+                                                    .format("%Y-%m-%d %H:%M %Z")
+                                                    .to_string()}
+                                            </p>
+                                        </div>
+                                        <ActionForm action=restore_journal>
+                                            <input
+                                                type="hidden"
+                                                name="journal_id"
+                                                value=journal.id.to_string()
+                                            />
+                                            <button
+                                                type="submit"
+                                                class="text-sm font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300"
+                                            >
+                                                "Restore"
+                                            </button>
+                                        </ActionForm>
+                                    </div>
+                                }
+                            })
+                            .collect_view()}
+                    }
+                        .into_any()
+                })}
+            </Suspense>
         </Layout>
     }
 }
 
+/// renaming and deleting, for users holding `Permissions::DELETE`
+#[component]
+fn JournalSettings(
+    journal_id: String,
+    name: String,
+    rename_journal: ServerAction<main_api::RenameJournal>,
+    delete_journal: ServerAction<main_api::DeleteJournal>,
+) -> impl IntoView {
+    let delete_journal_id = journal_id.clone();
+
+    view! {
+        <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
+        <ActionForm action=rename_journal attr:class="space-y-6">
+            <input type="hidden" name="journal_id" value=journal_id />
+            {ActionError(rename_journal, "renaming the journal")}
+            <div>
+                <label
+                    for="name"
+                    class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                >
+                    "Journal Name"
+                </label>
+                <div class="mt-2">
+                    <input
+                        id="name"
+                        type="text"
+                        name="name"
+                        value=name
+                        required
+                        class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
+                    />
+                </div>
+            </div>
+            <div>
+                <button
+                    type="submit"
+                    class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500"
+                >
+                    "Rename Journal"
+                </button>
+            </div>
+        </ActionForm>
+        <ActionForm action=delete_journal attr:class="mt-6">
+            <input type="hidden" name="journal_id" value=delete_journal_id />
+            {ActionError(delete_journal, "deleting the journal")}
+            <button
+                type="submit"
+                class="flex w-full justify-center rounded-md bg-red-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-red-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-red-600 dark:bg-red-500 dark:shadow-none dark:hover:bg-red-400"
+            >
+                "Delete Journal"
+            </button>
+            <p class="mt-2 text-sm text-gray-600 dark:text-gray-400">
+                {format!(
+                    "Deleted journals can be restored from the journal list for {} days.",
+                    RESTORE_GRACE_DAYS,
+                )}
+            </p>
+        </ActionForm>
+    }
+}
+
 #[component]
 pub fn JournalDetail() -> impl IntoView {
     use leptos_router::hooks::use_params_map;
@@ -116,6 +241,10 @@ pub fn JournalDetail() -> impl IntoView {
         |_| async move { main_api::get_associated_journals().await },
     );
 
+    let select_journal = ServerAction::<main_api::SelectJournal>::new();
+    let rename_journal = ServerAction::<main_api::RenameJournal>::new();
+    let delete_journal = ServerAction::<main_api::DeleteJournal>::new();
+
     view! {
         <Suspense>
             {move || Suspend::new(async move {
@@ -127,6 +256,10 @@ pub fn JournalDetail() -> impl IntoView {
                         return HandleError(e, "fetching journals").into_any();
                     }
                 };
+                let is_selected = journals
+                    .selected
+                    .as_ref()
+                    .is_some_and(|j| j.get_id().to_string() == journal_id());
                 let Some(journal) = journals
                     .associated
                     .into_iter()
@@ -135,9 +268,12 @@ pub fn JournalDetail() -> impl IntoView {
                 };
                 let journal_owner_resource = Resource::new(
                     move || (),
-                    move |_| async move { main_api::get_journal_owner(journal_id()).await },
+                    move |_| async move {
+                        main_api::get_journal_owner(Uuid::try_parse(&journal_id())?).await
+                    },
                 );
                 let journal_owner = journal_owner_resource.await;
+                let can_delete = journal.has_permission(Permissions::DELETE);
                 view! {
                     <Layout
                         page_title=journal.clone().get_name()
@@ -164,7 +300,25 @@ pub fn JournalDetail() -> impl IntoView {
                         </a>
 
                         <a
-                            href=format!("/journal{}/person", journal_id())
+                            href=format!("/journal/{}/rate", journal_id())
+                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"
+                        >
+                            <h3 class="text-lg font-semibold text-gray-900 dark:text-white">
+                                "Exchange Rates"
+                            </h3>
+                        </a>
+
+                        <a
+                            href=format!("/journal/{}/reports", journal_id())
+                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"
+                        >
+                            <h3 class="text-lg font-semibold text-gray-900 dark:text-white">
+                                "Reports"
+                            </h3>
+                        </a>
+
+                        <a
+                            href=format!("/journal/{}/person", journal_id())
                             class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"
                         >
                             <h3 class="text-lg font-semibold text-gray-900 dark:text-white">
@@ -190,6 +344,36 @@ pub fn JournalDetail() -> impl IntoView {
                             </div>
                         </div>
 
+                        <Show
+                            when=move || !is_selected
+                            fallback=|| {
+                                view! {
+                                    <p class="mt-4 text-sm text-gray-600 dark:text-gray-400">
+                                        "This journal opens by default."
+                                    </p>
+                                }
+                            }
+                        >
+                            <ActionForm action=select_journal attr:class="mt-4">
+                                <input type="hidden" name="journal_id" value=journal_id() />
+                                <button
+                                    type="submit"
+                                    class="text-sm font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300"
+                                >
+                                    "Open this journal by default"
+                                </button>
+                            </ActionForm>
+                        </Show>
+
+                        <Show when=move || can_delete>
+                            <JournalSettings
+                                journal_id=journal_id()
+                                name=journal.get_name()
+                                rename_journal=rename_journal
+                                delete_journal=delete_journal
+                            />
+                        </Show>
+
                     </Layout>
                 }
                     .into_any()
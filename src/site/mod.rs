@@ -5,4 +5,6 @@ mod handle_error;
 mod journal;
 mod layout;
 mod person;
+mod rate;
+mod report;
 mod transaction;
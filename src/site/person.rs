@@ -1,47 +1,89 @@
+use super::handle_error::{ActionError, HandleError};
 use super::layout::Layout;
+use crate::api::main_api;
+use crate::event_sourcing::journal::Permissions;
 use leptos::prelude::*;
 use leptos_router::hooks::use_params_map;
 use uuid::Uuid;
 
-struct Person {
-    pub id: Uuid,
-    pub username: String,
-}
-
-fn people() -> Vec<Person> {
-    use std::str::FromStr;
+/// the permission sets offered when inviting someone. they're submitted as json,
+/// which is what `invite_to_journal` parses
+fn invite_presets() -> Vec<(&'static str, Permissions)> {
     vec![
-        Person {
-            id: Uuid::from_str("250e8400-e29b-41d4-a716-446655440000").expect("Invalid UUID"),
-            username: "johndoe".to_string(),
-        },
-        Person {
-            id: Uuid::from_str("250e8400-e29b-41d4-a716-446655440001").expect("Invalid UUID"),
-            username: "janesmith".to_string(),
-        },
-        Person {
-            id: Uuid::from_str("250e8400-e29b-41d4-a716-446655440002").expect("Invalid UUID"),
-            username: "bobjohnson".to_string(),
-        },
+        ("Read only", Permissions::READ),
+        (
+            "Add transactions",
+            Permissions::READ | Permissions::APPENDTRANSACTION,
+        ),
+        (
+            "Add accounts and transactions",
+            Permissions::READ | Permissions::ADDACCOUNT | Permissions::APPENDTRANSACTION,
+        ),
+        ("Full access", Permissions::all()),
     ]
 }
 
-fn journals() -> Vec<super::journal::Journal> {
-    use std::str::FromStr;
-    vec![
-        super::journal::Journal {
-            id: Uuid::from_str("550e8400-e29b-41d4-a716-446655440000").expect("Invalid UUID"),
-            name: "Personal".to_string(),
-            creator_username: "johndoe".to_string(),
-            created_at: "2024-01-15 09:30:45".to_string(),
-        },
-        super::journal::Journal {
-            id: Uuid::from_str("550e8400-e29b-41d4-a716-446655440001").expect("Invalid UUID"),
-            name: "Business".to_string(),
-            creator_username: "janesmith".to_string(),
-            created_at: "2024-01-20 14:22:18".to_string(),
-        },
-    ]
+/// the preset's label if the permissions match one, otherwise the flags themselves
+fn permissions_label(permissions: Permissions) -> String {
+    invite_presets()
+        .into_iter()
+        .find(|(_, preset)| *preset == permissions)
+        .map(|(label, _)| label.to_string())
+        .unwrap_or_else(|| format!("{:?}", permissions))
+}
+
+/// lets the viewer change a tenant's permissions or remove them from the journal.
+/// the server still refuses changes beyond the viewer's own permissions
+#[component]
+fn TenantControls(
+    journal_id: String,
+    tenant_id: Uuid,
+    permissions: Permissions,
+    update_permissions: ServerAction<main_api::UpdateTenantPermissions>,
+    remove_from_journal: ServerAction<main_api::RemoveFromJournal>,
+) -> impl IntoView {
+    let remove_journal_id = journal_id.clone();
+
+    view! {
+        <div class="mt-3 flex flex-wrap items-center gap-3">
+            <ActionForm action=update_permissions attr:class="flex items-center gap-2">
+                <input type="hidden" name="journal_id" value=journal_id />
+                <input type="hidden" name="tenant_id" value=tenant_id.to_string() />
+                <select
+                    name="permissions"
+                    class="rounded-md bg-white px-2 py-1 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10"
+                >
+                    {invite_presets()
+                        .into_iter()
+                        .map(|(label, preset)| {
+                            let value = serde_json::to_string(&preset).unwrap_or_default();
+                            view! {
+                                <option value=value selected=preset == permissions>
+                                    {label}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+                <button
+                    type="submit"
+                    class="text-sm font-semibold text-indigo-600 hover:text-indigo-500 dark:text-indigo-400 dark:hover:text-indigo-300"
+                >
+                    "Update"
+                </button>
+            </ActionForm>
+            <ActionForm action=remove_from_journal>
+                <input type="hidden" name="journal_id" value=remove_journal_id />
+                <input type="hidden" name="tenant_id" value=tenant_id.to_string() />
+                <button
+                    type="submit"
+                    class="text-sm font-semibold text-red-600 hover:text-red-500 dark:text-red-400 dark:hover:text-red-300"
+                >
+                    "Remove"
+                </button>
+            </ActionForm>
+        </div>
+    }
 }
 
 #[component]
@@ -49,62 +91,151 @@ pub fn PeopleListPage() -> impl IntoView {
     let params = use_params_map();
     let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
 
-    let journal_name = move || {
-        journals()
-            .into_iter()
-            .find(|j| j.id.to_string() == journal_id())
-            .map(|j| j.name)
-            .unwrap_or_else(|| "Unknown Journal".to_string())
-    };
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    let members_resource = Resource::new(journal_id, |journal_id| async move {
+        main_api::get_journal_members(Uuid::try_parse(&journal_id)?).await
+    });
+
+    let invite_to_journal = ServerAction::<main_api::InviteToJournal>::new();
+    let update_permissions = ServerAction::<main_api::UpdateTenantPermissions>::new();
+    let remove_from_journal = ServerAction::<main_api::RemoveFromJournal>::new();
 
     view! {
-        <Layout page_title=journal_name() show_switch_link=true journal_id=journal_id()>
-            {people()
-                .into_iter()
-                .map(|person| {
-                    view! {
-                        <a
-                            href=format!("/journal/{}/person/{}", journal_id(), person.id)
-                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"
-                        >
-                            <h3 class="text-lg font-semibold text-gray-900 dark:text-white">
-                                {person.username}
-                            </h3>
-                        </a>
-                    }
-                })
-                .collect_view()}
-            <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
-            <div class="mt-10">
-                <form class="space-y-6">
-                    <div>
-                        <label
-                            for="username"
-                            class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
-                        >
-                            "Invite Person"
-                        </label>
-                        <div class="mt-2">
-                            <input
-                                id="username"
-                                type="text"
-                                name="username"
-                                required
-                                placeholder="Enter username to invite"
-                                class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
-                            />
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+                let can_manage = journal.has_permission(Permissions::INVITE);
+                let members = match members_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching people").into_any(),
+                };
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        // every tenant's controls share these actions, so refusals show once up top
+                        {can_manage
+                            .then(|| {
+                                view! {
+                                    {ActionError(update_permissions, "updating permissions")}
+                                    {ActionError(remove_from_journal, "removing the person")}
+                                }
+                            })}
+                        {members
+                            .into_iter()
+                            .map(|member| {
+                                let controls = member
+                                    .tenant_info
+                                    .as_ref()
+                                    .filter(|_| can_manage)
+                                    .map(|tenant_info| {
+                                        view! {
+                                            <TenantControls
+                                                journal_id=journal_id()
+                                                tenant_id=member.id
+                                                permissions=tenant_info.tenant_permissions
+                                                update_permissions=update_permissions
+                                                remove_from_journal=remove_from_journal
+                                            />
+                                        }
+                                    });
+                                view! {
+                                    <div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl">
+                                        <div class="flex justify-between items-center">
+                                            <h3 class="text-lg font-semibold text-gray-900 dark:text-white">
+                                                {member.username}
+                                            </h3>
+                                            <span class="text-sm text-gray-500 dark:text-gray-400">
+                                                {match &member.tenant_info {
+                                                    None => "Owner".to_string(),
+                                                    Some(tenant_info) => {
+                                                        permissions_label(tenant_info.tenant_permissions)
+                                                    }
+                                                }}
+                                            </span>
+                                        </div>
+                                        {controls}
+                                    </div>
+                                }
+                            })
+                            .collect_view()}
+                        <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
+                        <div class="mt-10">
+                            <ActionForm action=invite_to_journal attr:class="space-y-6">
+                                <input type="hidden" name="journal_id" value=journal_id() />
+                                {ActionError(invite_to_journal, "sending the invite")}
+                                <div>
+                                    <label
+                                        for="invitee_username"
+                                        class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                    >
+                                        "Invite Person"
+                                    </label>
+                                    <div class="mt-2">
+                                        <input
+                                            id="invitee_username"
+                                            type="text"
+                                            name="invitee_username"
+                                            required
+                                            placeholder="Enter username to invite"
+                                            class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
+                                        />
+                                    </div>
+                                </div>
+                                <div>
+                                    <label
+                                        for="permissions"
+                                        class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                    >
+                                        "Permissions"
+                                    </label>
+                                    <div class="mt-2">
+                                        <select
+                                            id="permissions"
+                                            name="permissions"
+                                            class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:focus:outline-indigo-500"
+                                        >
+                                            {invite_presets()
+                                                .into_iter()
+                                                .map(|(label, permissions)| {
+                                                    let value = serde_json::to_string(&permissions)
+                                                        .unwrap_or_default();
+                                                    view! { <option value=value>{label}</option> }
+                                                })
+                                                .collect_view()}
+                                        </select>
+                                    </div>
+                                </div>
+                                <div>
+                                    <button
+                                        type="submit"
+                                        class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500"
+                                    >
+                                        "Send Invite"
+                                    </button>
+                                </div>
+                            </ActionForm>
                         </div>
-                    </div>
-                    <div>
-                        <button
-                            type="submit"
-                            class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500"
-                        >
-                            "Send Invite"
-                        </button>
-                    </div>
-                </form>
-            </div>
-        </Layout>
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
     }
 }
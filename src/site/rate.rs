@@ -0,0 +1,165 @@
+use super::handle_error::{ActionError, HandleError};
+use super::layout::Layout;
+use crate::api::main_api;
+use crate::event_sourcing::currency::Currency;
+use crate::event_sourcing::journal::Permissions;
+use leptos::prelude::*;
+use leptos_router::hooks::use_params_map;
+use uuid::Uuid;
+
+const SELECT_CLASS: &str = "block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:focus:outline-indigo-500";
+
+/// a select with every currency, starting on `selected`
+fn currency_select(id: &'static str, selected: Currency) -> impl IntoView {
+    view! {
+        <select id=id name=id class=SELECT_CLASS>
+            {Currency::ALL
+                .into_iter()
+                .map(|currency| {
+                    view! {
+                        <option value=currency.code() selected=currency == selected>
+                            {currency.code()}
+                        </option>
+                    }
+                })
+                .collect_view()}
+        </select>
+    }
+}
+
+/// the journal's exchange rates, which entries that mix currencies are checked against
+#[component]
+pub fn ExchangeRatesPage() -> impl IntoView {
+    let params = use_params_map();
+    let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
+
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    let rates_resource = Resource::new(journal_id, |journal_id| async move {
+        main_api::get_exchange_rates(Uuid::try_parse(&journal_id)?).await
+    });
+
+    let record_rate = ServerAction::<main_api::RecordExchangeRate>::new();
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+                let mut rates = match rates_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching exchange rates").into_any(),
+                };
+                rates.sort_by_key(|rate| (rate.base.code(), rate.quote.code()));
+                let can_record = journal.has_permission(Permissions::APPENDTRANSACTION);
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        {if rates.is_empty() {
+                            view! {
+                                <p class="text-sm text-gray-500 dark:text-gray-400">
+                                    "No exchange rates yet. Entries that mix currencies need one for each pair."
+                                </p>
+                            }
+                                .into_any()
+                        } else {
+                            rates
+                                .into_iter()
+                                .map(|rate| {
+                                    view! {
+                                        <div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl text-lg font-medium text-gray-900 dark:text-white">
+                                            {rate.display()}
+                                        </div>
+                                    }
+                                })
+                                .collect_view()
+                                .into_any()
+                        }}
+                        {can_record
+                            .then(|| {
+                                view! {
+                                    <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
+                                    <div class="mt-10">
+                                        <ActionForm action=record_rate attr:class="space-y-6">
+                                            <input type="hidden" name="journal_id" value=journal_id() />
+                                            <h3 class="text-sm/6 font-medium text-gray-900 dark:text-gray-100">
+                                                "Record Exchange Rate"
+                                            </h3>
+                                            {ActionError(record_rate, "recording the exchange rate")}
+                                            <div class="grid grid-cols-3 gap-3">
+                                                <div>
+                                                    <label
+                                                        for="base"
+                                                        class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                                    >
+                                                        "1 unit of"
+                                                    </label>
+                                                    <div class="mt-2">
+                                                        {currency_select("base", Currency::Eur)}
+                                                    </div>
+                                                </div>
+                                                <div>
+                                                    <label
+                                                        for="rate"
+                                                        class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                                    >
+                                                        "is worth"
+                                                    </label>
+                                                    <div class="mt-2">
+                                                        <input
+                                                            id="rate"
+                                                            type="text"
+                                                            name="rate"
+                                                            required
+                                                            inputmode="decimal"
+                                                            placeholder="1.0845"
+                                                            class="block w-full rounded-md bg-white px-3 py-1.5 text-base text-gray-900 outline-1 -outline-offset-1 outline-gray-300 placeholder:text-gray-400 focus:outline-2 focus:-outline-offset-2 focus:outline-indigo-600 sm:text-sm/6 dark:bg-white/5 dark:text-white dark:outline-white/10 dark:placeholder:text-gray-500 dark:focus:outline-indigo-500"
+                                                        />
+                                                    </div>
+                                                </div>
+                                                <div>
+                                                    <label
+                                                        for="quote"
+                                                        class="block text-sm/6 font-medium text-gray-900 dark:text-gray-100"
+                                                    >
+                                                        "units of"
+                                                    </label>
+                                                    <div class="mt-2">
+                                                        {currency_select("quote", Currency::Usd)}
+                                                    </div>
+                                                </div>
+                                            </div>
+                                            <div>
+                                                <button
+                                                    type="submit"
+                                                    class="flex w-full justify-center rounded-md bg-indigo-600 px-3 py-1.5 text-sm/6 font-semibold text-white shadow-xs hover:bg-indigo-500 focus-visible:outline-2 focus-visible:outline-offset-2 focus-visible:outline-indigo-600 dark:bg-indigo-500 dark:shadow-none dark:hover:bg-indigo-400 dark:focus-visible:outline-indigo-500"
+                                                >
+                                                    "Record Rate"
+                                                </button>
+                                            </div>
+                                        </ActionForm>
+                                    </div>
+                                }
+                            })}
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
+    }
+}
@@ -0,0 +1,438 @@
+use super::handle_error::HandleError;
+use super::layout::Layout;
+use crate::api::main_api;
+use crate::api::return_types::{Account, CurrencyTotal, ReportSection};
+use crate::event_sourcing::currency::Currency;
+use chrono::{Datelike, NaiveDate};
+use leptos::prelude::*;
+use leptos_router::hooks::{use_params_map, use_query_map};
+use std::cmp::Ordering;
+use uuid::Uuid;
+
+const INPUT_CLASS: &str = "rounded-md bg-white px-3 py-1.5 text-sm text-gray-900 outline-1 -outline-offset-1 outline-gray-300 dark:bg-white/5 dark:text-white dark:outline-white/10";
+
+/// today in Chicago, the time zone the rest of the site reads dates in
+fn today() -> NaiveDate {
+    chrono::Utc::now()
+        .with_timezone(&chrono_tz::America::Chicago)
+        .date_naive()
+}
+
+/// a date from the query string, or `default` if it's missing or malformed
+fn query_date(key: &'static str, default: fn() -> NaiveDate) -> impl Fn() -> NaiveDate + Copy {
+    let query = use_query_map();
+    move || {
+        query
+            .get()
+            .get(key)
+            .and_then(|date| date.parse().ok())
+            .unwrap_or_else(default)
+    }
+}
+
+/// a labelled date field for the report's filter form
+fn date_field(name: &'static str, label: &'static str, value: NaiveDate) -> impl IntoView {
+    view! {
+        <label class="block text-sm font-medium text-gray-700 dark:text-gray-300">
+            {label} <input type="date" name=name value=value.to_string() class=INPUT_CLASS />
+        </label>
+    }
+}
+
+fn submit_button() -> impl IntoView {
+    view! {
+        <button
+            type="submit"
+            class="rounded-md bg-indigo-600 px-3 py-1.5 text-sm font-semibold text-white shadow-xs hover:bg-indigo-500 dark:bg-indigo-500 dark:hover:bg-indigo-400"
+        >
+            "Show"
+        </button>
+    }
+}
+
+fn heading(title: &'static str, period: String) -> impl IntoView {
+    view! {
+        <div class="mb-4">
+            <h1 class="text-xl font-semibold text-gray-900 dark:text-white">{title}</h1>
+            <p class="text-sm text-gray-500 dark:text-gray-400">{period}</p>
+        </div>
+    }
+}
+
+/// an account and its sub-accounts, indented by depth, with the rolled up total on the
+/// account type's normal side
+fn account_rows(account: Account, depth: usize) -> AnyView {
+    let amount = account
+        .currency
+        .format(account.account_type.normal_balance(account.total_balance));
+    let weight = if account.children.is_empty() {
+        "font-normal"
+    } else {
+        "font-medium"
+    };
+
+    view! {
+        <div class=format!("flex justify-between py-1 text-gray-900 dark:text-white {}", weight)>
+            <span style=format!("padding-left: {}rem", depth * 2)>{account.name}</span>
+            <span>{amount}</span>
+        </div>
+        {account.children.into_iter().map(|child| account_rows(child, depth + 1)).collect_view()}
+    }
+    .into_any()
+}
+
+fn total_rows(label: String, totals: Vec<CurrencyTotal>) -> impl IntoView {
+    totals
+        .into_iter()
+        .map(|total| {
+            view! {
+                <div class="flex justify-between py-1 font-semibold text-gray-900 dark:text-white">
+                    <span>{format!("{} ({})", label, total.currency.code())}</span>
+                    <span>{total.currency.format(total.amount)}</span>
+                </div>
+            }
+        })
+        .collect_view()
+}
+
+fn section(section: ReportSection) -> impl IntoView {
+    let plural = section.account_type.plural();
+
+    view! {
+        <div class="mt-6 p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl">
+            <h2 class="mb-2 text-base font-semibold text-gray-900 dark:text-white">{plural}</h2>
+            {if section.accounts.is_empty() {
+                view! { <p class="text-sm text-gray-500 dark:text-gray-400">"Nothing to show"</p> }
+                    .into_any()
+            } else {
+                section
+                    .accounts
+                    .into_iter()
+                    .map(|account| account_rows(account, 0))
+                    .collect_view()
+                    .into_any()
+            }}
+            <div class="mt-2 border-t border-gray-200 dark:border-gray-700">
+                {total_rows(format!("Total {}", plural.to_lowercase()), section.totals)}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+pub fn ReportsPage() -> impl IntoView {
+    let params = use_params_map();
+    let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
+
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        {[
+                            ("trial-balance", "Trial Balance"),
+                            ("balance-sheet", "Balance Sheet"),
+                            ("income-statement", "Income Statement"),
+                        ]
+                            .into_iter()
+                            .map(|(path, title)| {
+                                view! {
+                                    <a
+                                        href=format!("/journal/{}/reports/{}", journal_id(), path)
+                                        class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"
+                                    >
+                                        <h3 class="text-lg font-semibold text-gray-900 dark:text-white">
+                                            {title}
+                                        </h3>
+                                    </a>
+                                }
+                            })
+                            .collect_view()}
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+pub fn TrialBalancePage() -> impl IntoView {
+    let params = use_params_map();
+    let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
+    let as_of = query_date("as_of", today);
+
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    let report_resource = Resource::new(
+        move || (journal_id(), as_of()),
+        |(journal_id, as_of)| async move {
+            main_api::get_trial_balance(Uuid::try_parse(&journal_id)?, as_of).await
+        },
+    );
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+                let report = match report_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "building the trial balance").into_any(),
+                };
+                let mut currencies: Vec<Currency> = report
+                    .debits
+                    .iter()
+                    .chain(&report.credits)
+                    .map(|total| total.currency)
+                    .collect();
+                currencies.sort_by_key(|currency| currency.code());
+                currencies.dedup();
+                let column_total = |totals: &[CurrencyTotal], currency: Currency| {
+                    let amount = totals
+                        .iter()
+                        .find(|total| total.currency == currency)
+                        .map(|total| total.amount)
+                        .unwrap_or_default();
+                    currency.format(amount)
+                };
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        {heading("Trial Balance", format!("As of {}", report.as_of))}
+                        <form method="get" class="mb-6 flex items-end gap-3">
+                            {date_field("as_of", "As of", report.as_of)}
+                            {submit_button()}
+                        </form>
+                        <table class="w-full text-left text-gray-900 dark:text-white">
+                            <thead class="text-sm text-gray-500 dark:text-gray-400">
+                                <tr>
+                                    <th class="py-2 font-medium">"Account"</th>
+                                    <th class="py-2 font-medium">"Type"</th>
+                                    <th class="py-2 font-medium text-right">"Debit"</th>
+                                    <th class="py-2 font-medium text-right">"Credit"</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {report
+                                    .accounts
+                                    .into_iter()
+                                    .map(|account| {
+                                        let (debit, credit) = match account.balance.cmp(&0) {
+                                            Ordering::Greater => {
+                                                (Some(account.currency.format(account.balance)), None)
+                                            }
+                                            Ordering::Less => {
+                                                (
+                                                    None,
+                                                    Some(
+                                                        account.currency.format(account.balance.saturating_neg()),
+                                                    ),
+                                                )
+                                            }
+                                            Ordering::Equal => (None, None),
+                                        };
+                                        view! {
+                                            <tr class="border-t border-gray-200 dark:border-gray-700">
+                                                <td class="py-1">{account.name}</td>
+                                                <td class="py-1 text-sm text-gray-500 dark:text-gray-400">
+                                                    {account.account_type.name()}
+                                                </td>
+                                                <td class="py-1 text-right">{debit}</td>
+                                                <td class="py-1 text-right">{credit}</td>
+                                            </tr>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </tbody>
+                            <tfoot class="font-semibold">
+                                {currencies
+                                    .into_iter()
+                                    .map(|currency| {
+                                        view! {
+                                            <tr class="border-t-2 border-gray-300 dark:border-gray-600">
+                                                <td class="py-1">
+                                                    {format!("Total ({})", currency.code())}
+                                                </td>
+                                                <td></td>
+                                                <td class="py-1 text-right">
+                                                    {column_total(&report.debits, currency)}
+                                                </td>
+                                                <td class="py-1 text-right">
+                                                    {column_total(&report.credits, currency)}
+                                                </td>
+                                            </tr>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </tfoot>
+                        </table>
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+pub fn BalanceSheetPage() -> impl IntoView {
+    let params = use_params_map();
+    let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
+    let as_of = query_date("as_of", today);
+
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    let report_resource = Resource::new(
+        move || (journal_id(), as_of()),
+        |(journal_id, as_of)| async move {
+            main_api::get_balance_sheet(Uuid::try_parse(&journal_id)?, as_of).await
+        },
+    );
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+                let report = match report_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "building the balance sheet").into_any(),
+                };
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        {heading("Balance Sheet", format!("As of {}", report.as_of))}
+                        <form method="get" class="flex items-end gap-3">
+                            {date_field("as_of", "As of", report.as_of)}
+                            {submit_button()}
+                        </form>
+                        {report.sections.into_iter().map(section).collect_view()}
+                        <div class="mt-6 p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl">
+                            <h2 class="mb-2 text-base font-semibold text-gray-900 dark:text-white">
+                                "Retained Earnings"
+                            </h2>
+                            <p class="mb-2 text-sm text-gray-500 dark:text-gray-400">
+                                "Income less expenses, counted with equity"
+                            </p>
+                            {total_rows("Retained earnings".to_string(), report.retained_earnings)}
+                        </div>
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+pub fn IncomeStatementPage() -> impl IntoView {
+    let params = use_params_map();
+    let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
+    // the year so far, unless the query asks for another period
+    let from = query_date("from", || today().with_ordinal(1).unwrap_or_default());
+    let to = query_date("to", today);
+
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    let report_resource = Resource::new(
+        move || (journal_id(), from(), to()),
+        |(journal_id, from, to)| async move {
+            main_api::get_income_statement(Uuid::try_parse(&journal_id)?, from, to).await
+        },
+    );
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+                let report = match report_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "building the income statement").into_any(),
+                };
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        {heading("Income Statement", format!("{} to {}", report.from, report.to))}
+                        <form method="get" class="flex items-end gap-3">
+                            {date_field("from", "From", report.from)}
+                            {date_field("to", "To", report.to)}
+                            {submit_button()}
+                        </form>
+                        {report.sections.into_iter().map(section).collect_view()}
+                        <div class="mt-6 p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl">
+                            {total_rows("Net income".to_string(), report.net_income)}
+                        </div>
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
+    }
+}
@@ -1,389 +1,603 @@
+use super::handle_error::{ActionError, HandleError};
 use super::layout::Layout;
+use crate::api::main_api;
+use crate::api::return_types::{Account, TransactionLine};
+use crate::event_sourcing::journal::Permissions;
+use chrono::NaiveDate;
 use leptos::prelude::*;
 use leptos_router::hooks::use_params_map;
+use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(Debug, Clone)]
-enum EntryType {
-    Debit,
-    Credit,
-}
+const INPUT_CLASS: &str = "w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400";
 
-#[derive(Debug, Clone)]
-struct Entry {
-    pub account: AccountItem,
-    pub amount: i64, // in cents
-    pub entry_type: EntryType,
-}
+/// one line of the new transaction form, `index` is its position in the submitted vectors.
+/// `line` fills it in, so a correction starts from what's being corrected
+#[component]
+fn EntryRow(
+    index: usize,
+    accounts: Vec<Account>,
+    optional: bool,
+    #[prop(optional_no_strip)] line: Option<TransactionLine>,
+) -> impl IntoView {
+    let selected = line.as_ref().map(|line| line.account_id);
+    let amount = |debit: bool| {
+        line.as_ref()
+            .filter(|line| (line.changed_by > 0) == debit)
+            .map(|line| line.currency.decimal(line.changed_by.unsigned_abs()))
+    };
+    let (debit, credit) = (amount(true), amount(false));
+    let memo = line.and_then(|line| line.memo);
 
-#[derive(Debug, Clone)]
-struct AccountItem {
-    pub id: Uuid,
-    pub name: String,
+    view! {
+        <div class="p-4 bg-gray-50 dark:bg-gray-700 rounded-lg space-y-3">
+            <div>
+                <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
+                    {if optional { "Account (Optional)" } else { "Account" }}
+                </label>
+                <select
+                    name=format!("account_ids[{}]", index)
+                    class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400"
+                >
+                    <option value="">"Select account..."</option>
+                    {accounts
+                        .into_iter()
+                        .map(|account| {
+                            view! {
+                                <option
+                                    value=account.id.to_string()
+                                    selected=selected == Some(account.id)
+                                >
+                                    {format!("{} ({})", account.name, account.currency.code())}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </div>
+            <div class="grid grid-cols-2 gap-2 sm:gap-3">
+                <div>
+                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
+                        "Dr"
+                    </label>
+                    <input
+                        type="text"
+                        inputmode="decimal"
+                        name=format!("balance_add_cents[{}]", index)
+                        value=debit
+                        placeholder="0.00"
+                        class=format!("{} text-right", INPUT_CLASS)
+                    />
+                </div>
+                <div>
+                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
+                        "Cr"
+                    </label>
+                    <input
+                        type="text"
+                        inputmode="decimal"
+                        name=format!("balance_remove_cents[{}]", index)
+                        value=credit
+                        placeholder="0.00"
+                        class=format!("{} text-right", INPUT_CLASS)
+                    />
+                </div>
+            </div>
+            <input
+                type="text"
+                name=format!("memos[{}]", index)
+                value=memo
+                placeholder="Memo (optional)"
+                class=INPUT_CLASS
+            />
+        </div>
+    }
 }
 
-#[derive(Debug, Clone)]
-struct Person {
-    pub id: Uuid,
-    pub username: String,
-}
+#[component]
+pub fn TransactionListPage() -> impl IntoView {
+    let params = use_params_map();
+    let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
 
-#[derive(Debug, Clone)]
-struct Transaction {
-    pub id: Uuid,
-    pub author: Person,
-    pub entries: Vec<Entry>,
-}
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
 
-fn transactions() -> Vec<Transaction> {
-    use std::str::FromStr;
-    vec![
-        Transaction {
-            id: Uuid::from_str("350e8400-e29b-41d4-a716-446655440000").expect("Invalid UUID"),
-            author: Person {
-                id: Uuid::from_str("250e8400-e29b-41d4-a716-446655440000").expect("Invalid UUID"),
-                username: "johndoe".to_string(),
-            },
-            entries: vec![
-                Entry {
-                    account: AccountItem {
-                        id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440000")
-                            .expect("Invalid UUID"),
-                        name: "Cash".to_string(),
-                    },
-                    amount: 4567, // $45.67 in cents
-                    entry_type: EntryType::Credit,
-                },
-                Entry {
-                    account: AccountItem {
-                        id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440002")
-                            .expect("Invalid UUID"),
-                        name: "Groceries Expense".to_string(),
-                    },
-                    amount: 4567, // $45.67 in cents
-                    entry_type: EntryType::Debit,
-                },
-            ],
-        },
-        Transaction {
-            id: Uuid::from_str("350e8400-e29b-41d4-a716-446655440001").expect("Invalid UUID"),
-            author: Person {
-                id: Uuid::from_str("250e8400-e29b-41d4-a716-446655440001").expect("Invalid UUID"),
-                username: "janesmith".to_string(),
-            },
-            entries: vec![
-                Entry {
-                    account: AccountItem {
-                        id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440001")
-                            .expect("Invalid UUID"),
-                        name: "Checking Account".to_string(),
-                    },
-                    amount: 3214, // $32.14 in cents
-                    entry_type: EntryType::Credit,
-                },
-                Entry {
-                    account: AccountItem {
-                        id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440003")
-                            .expect("Invalid UUID"),
-                        name: "Fuel Expense".to_string(),
-                    },
-                    amount: 3214, // $32.14 in cents
-                    entry_type: EntryType::Debit,
-                },
-            ],
-        },
-        Transaction {
-            id: Uuid::from_str("350e8400-e29b-41d4-a716-446655440002").expect("Invalid UUID"),
-            author: Person {
-                id: Uuid::from_str("250e8400-e29b-41d4-a716-446655440002").expect("Invalid UUID"),
-                username: "bobjohnson".to_string(),
-            },
-            entries: vec![
-                Entry {
-                    account: AccountItem {
-                        id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440000")
-                            .expect("Invalid UUID"),
-                        name: "Cash".to_string(),
-                    },
-                    amount: 425, // $4.25 in cents
-                    entry_type: EntryType::Credit,
-                },
-                Entry {
-                    account: AccountItem {
-                        id: Uuid::from_str("450e8400-e29b-41d4-a716-446655440004")
-                            .expect("Invalid UUID"),
-                        name: "Coffee Expense".to_string(),
-                    },
-                    amount: 425, // $4.25 in cents
-                    entry_type: EntryType::Debit,
-                },
-            ],
-        },
-    ]
+    let accounts_resource = Resource::new(journal_id, |journal_id| async move {
+        main_api::get_accounts(Uuid::try_parse(&journal_id)?).await
+    });
+
+    let transactions_resource = Resource::new(journal_id, |journal_id| async move {
+        main_api::get_transactions(Uuid::try_parse(&journal_id)?).await
+    });
+
+    let transact = ServerAction::<main_api::Transact>::new();
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let journals = match journals_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching journals").into_any(),
+                };
+                let Some(journal) = journals
+                    .associated
+                    .into_iter()
+                    .find(|j| j.get_id().to_string() == journal_id()) else {
+                    return view! { <p>"Unable to find journal"</p> }.into_any()
+                };
+                let mut accounts = match accounts_resource.await {
+                    Ok(s) => Account::flatten(s),
+                    Err(e) => return HandleError(e, "fetching accounts").into_any(),
+                };
+                accounts.sort_unstable_by_key(|account| account.name.clone());
+                let transactions = match transactions_resource.await {
+                    Ok(s) => s,
+                    Err(e) => return HandleError(e, "fetching transactions").into_any(),
+                };
+                let accounts_by_id: HashMap<Uuid, Account> = accounts
+                    .iter()
+                    .map(|account| (account.id, account.clone()))
+                    .collect();
+                accounts.retain(|account| !account.archived);
+
+                view! {
+                    <Layout
+                        page_title=journal.get_name()
+                        show_switch_link=true
+                        journal_id=journal_id()
+                    >
+                        {transactions
+                            .into_iter()
+                            .rev()
+                            .map(|bundled| {
+                                let transaction = bundled.transaction;
+                                view! {
+                                    <a
+                                        href=format!(
+                                            "/journal/{}/transaction/{}",
+                                            journal_id(),
+                                            transaction.id,
+                                        )
+                                        class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"
+                                    >
+                                        <div class="space-y-3">
+                                            <div class="flex justify-between items-center text-sm text-gray-600 dark:text-gray-400">
+                                                <span>{transaction.description.clone()}</span>
+                                                <span>
+                                                    {transaction.effective_date.to_string()}
+                                                    {transaction.reversal.as_ref().map(|_| " (reversed)")}
+                                                </span>
+                                            </div>
+                                            <div class="space-y-2">
+                                                {transaction
+                                                    .updates
+                                                    .iter()
+                                                    .map(|update| {
+                                                        let account = accounts_by_id.get(&update.account_id);
+                                                        let entry_amount = account
+                                                            .map(|a| a.currency.format(update.changed_by.abs()))
+                                                            .unwrap_or(update.changed_by.abs().to_string());
+                                                        let entry_type_str = if update.changed_by < 0 {
+                                                            "Cr"
+                                                        } else {
+                                                            "Dr"
+                                                        };
+                                                        view! {
+                                                            <div class="flex justify-between items-center">
+                                                                <span class="text-base font-medium text-gray-900 dark:text-white">
+                                                                    {account
+                                                                        .map(|a| a.name.clone())
+                                                                        .unwrap_or("unknown account".to_string())}
+                                                                </span>
+                                                                <span class="text-base text-gray-700 dark:text-gray-300">
+                                                                    {entry_amount} " " {entry_type_str}
+                                                                </span>
+                                                            </div>
+                                                        }
+                                                    })
+                                                    .collect_view()}
+                                            </div>
+                                            <div class="text-xs text-gray-400 dark:text-gray-500">
+                                                {transaction.author}
+                                            </div>
+                                        </div>
+                                    </a>
+                                }
+                            })
+                            .collect_view()}
+                        <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
+                        <div class="mt-10">
+                            <div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6">
+                                <h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-6">
+                                    "Create New Transaction"
+                                </h3>
+                                <ActionForm action=transact attr:class="space-y-6">
+                                    <input type="hidden" name="journal_id" value=journal_id() />
+                                    {ActionError(transact, "creating the transaction")}
+                                    <div class="grid grid-cols-3 gap-2 sm:gap-3">
+                                        <div class="col-span-2">
+                                            <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
+                                                "Description"
+                                            </label>
+                                            <input type="text" name="description" class=INPUT_CLASS />
+                                        </div>
+                                        <div>
+                                            <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
+                                                "Date"
+                                            </label>
+                                            <input
+                                                type="date"
+                                                name="effective_date"
+                                                required
+                                                value=chrono::Utc::now().date_naive().to_string()
+                                                class=INPUT_CLASS
+                                            />
+                                        </div>
+                                    </div>
+
+                                    // archived accounts still name old lines above, but can't be posted to
+                                    {(0..4)
+                                        .map(|index| {
+                                            let optional = index >= 2;
+                                            view! {
+                                                <EntryRow
+                                                    index=index
+                                                    accounts=accounts.clone()
+                                                    optional=optional
+                                                />
+                                            }
+                                        })
+                                        .collect_view()}
+
+                                    <div class="flex justify-between items-center pt-4 border-t border-gray-200 dark:border-gray-600">
+                                        <div class="text-sm text-gray-500 dark:text-gray-400">
+                                            "Debits must equal credits"
+                                        </div>
+                                        <button
+                                            type="submit"
+                                            class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800"
+                                        >
+                                            "Create Transaction"
+                                        </button>
+                                    </div>
+                                </ActionForm>
+                            </div>
+                        </div>
+                    </Layout>
+                }
+                    .into_any()
+            })}
+        </Suspense>
+    }
 }
 
-fn journals() -> Vec<super::journal::Journal> {
-    use std::str::FromStr;
-    vec![
-        super::journal::Journal {
-            id: Uuid::from_str("550e8400-e29b-41d4-a716-446655440000").expect("Invalid UUID"),
-            name: "Personal".to_string(),
-            creator_username: "johndoe".to_string(),
-            created_at: "2024-01-15 09:30:45".to_string(),
-        },
-        super::journal::Journal {
-            id: Uuid::from_str("550e8400-e29b-41d4-a716-446655440001").expect("Invalid UUID"),
-            name: "Business".to_string(),
-            creator_username: "janesmith".to_string(),
-            created_at: "2024-01-20 14:22:18".to_string(),
-        },
-    ]
+/// reversing and correcting an entry, for users who can add transactions. the correction
+/// starts out as a copy of the entry, with two spare rows
+#[component]
+fn EntryControls(
+    journal_id: String,
+    transaction_id: Uuid,
+    description: String,
+    effective_date: NaiveDate,
+    lines: Vec<TransactionLine>,
+    accounts: Vec<Account>,
+    reverse: ServerAction<main_api::ReverseTransaction>,
+    correct: ServerAction<main_api::CorrectTransaction>,
+) -> impl IntoView {
+    let correct_journal_id = journal_id.clone();
+    let row_count = lines.len().max(2) + 2;
+    let mut lines = lines.into_iter();
+
+    view! {
+        <div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6">
+            <h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-6">
+                "Reverse Transaction"
+            </h3>
+            <ActionForm action=reverse attr:class="space-y-4">
+                <input type="hidden" name="journal_id" value=journal_id />
+                <input type="hidden" name="transaction_id" value=transaction_id.to_string() />
+                <input type="text" name="reason" required placeholder="Reason" class=INPUT_CLASS />
+                <button
+                    type="submit"
+                    class="px-6 py-2 bg-red-600 text-white font-medium rounded-md hover:bg-red-700 dark:bg-red-500 dark:hover:bg-red-400"
+                >
+                    "Reverse"
+                </button>
+            </ActionForm>
+        </div>
+        <div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6">
+            <h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-6">
+                "Correct Transaction"
+            </h3>
+            <ActionForm action=correct attr:class="space-y-6">
+                <input type="hidden" name="journal_id" value=correct_journal_id />
+                <input type="hidden" name="transaction_id" value=transaction_id.to_string() />
+                <input type="text" name="reason" required placeholder="Reason" class=INPUT_CLASS />
+                <div class="grid grid-cols-3 gap-2 sm:gap-3">
+                    <div class="col-span-2">
+                        <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
+                            "Description"
+                        </label>
+                        <input type="text" name="description" value=description class=INPUT_CLASS />
+                    </div>
+                    <div>
+                        <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
+                            "Date"
+                        </label>
+                        <input
+                            type="date"
+                            name="effective_date"
+                            required
+                            value=effective_date.to_string()
+                            class=INPUT_CLASS
+                        />
+                    </div>
+                </div>
+                {(0..row_count)
+                    .map(|index| {
+                        let optional = index >= 2;
+                        view! {
+                            <EntryRow
+                                index=index
+                                accounts=accounts.clone()
+                                optional=optional
+                                line=lines.next()
+                            />
+                        }
+                    })
+                    .collect_view()}
+                <div class="flex justify-between items-center pt-4 border-t border-gray-200 dark:border-gray-600">
+                    <div class="text-sm text-gray-500 dark:text-gray-400">
+                        "The original is reversed and replaced by this"
+                    </div>
+                    <button
+                        type="submit"
+                        class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800"
+                    >
+                        "Correct Transaction"
+                    </button>
+                </div>
+            </ActionForm>
+        </div>
+    }
 }
 
 #[component]
-pub fn TransactionListPage() -> impl IntoView {
+pub fn TransactionDetailPage() -> impl IntoView {
     let params = use_params_map();
     let journal_id = move || params.get().get("id").unwrap_or_default().to_string();
+    let transaction_id = move || {
+        params
+            .get()
+            .get("transaction_id")
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let transaction_resource = Resource::new(
+        move || (journal_id(), transaction_id()),
+        |(journal_id, transaction_id)| async move {
+            let (Ok(journal_id), Ok(transaction_id)) = (
+                Uuid::try_parse(&journal_id),
+                Uuid::try_parse(&transaction_id),
+            ) else {
+                return Ok(None);
+            };
+            main_api::get_transaction(journal_id, transaction_id)
+                .await
+                .map(Some)
+        },
+    );
+
+    let journals_resource = Resource::new(
+        move || (),
+        |_| async move { main_api::get_associated_journals().await },
+    );
+
+    let accounts_resource = Resource::new(journal_id, |journal_id| async move {
+        main_api::get_accounts(Uuid::try_parse(&journal_id)?).await
+    });
+
+    let reverse = ServerAction::<main_api::ReverseTransaction>::new();
+    let correct = ServerAction::<main_api::CorrectTransaction>::new();
 
-    let journal_name = move || {
-        journals()
-            .into_iter()
-            .find(|j| j.id.to_string() == journal_id())
-            .map(|j| j.name)
-            .unwrap_or_else(|| "Unknown Journal".to_string())
+    let format_time = |time: chrono::DateTime<chrono::Utc>| {
+        time.with_timezone(&chrono_tz::America::Chicago)
+            .format("%Y-%m-%d %H:%M:%S %Z")
+            .to_string()
     };
 
     view! {
-        <Layout page_title=journal_name() show_switch_link=true journal_id=journal_id()>
-            {transactions()
-                .into_iter()
-                .map(|transaction| {
+        <Layout page_title="Transaction".to_string() show_switch_link=true journal_id=journal_id()>
+            <Suspense>
+                {move || Suspend::new(async move {
+                    let detail = match transaction_resource.await {
+                        Ok(Some(detail)) => detail,
+                        Ok(None) => {
+                            return view! { <p>"Unable to find transaction"</p> }.into_any();
+                        }
+                        Err(e) => return HandleError(e, "fetching the transaction").into_any(),
+                    };
+                    let journals = match journals_resource.await {
+                        Ok(s) => s,
+                        Err(e) => return HandleError(e, "fetching journals").into_any(),
+                    };
+                    let can_append = journals
+                        .associated
+                        .iter()
+                        .find(|j| j.get_id().to_string() == journal_id())
+                        .is_some_and(|j| j.has_permission(Permissions::APPENDTRANSACTION));
+                    let mut accounts = match accounts_resource.await {
+                        Ok(s) => Account::flatten(s),
+                        Err(e) => return HandleError(e, "fetching accounts").into_any(),
+                    };
+                    accounts.sort_unstable_by_key(|account| account.name.clone());
+                    accounts.retain(|account| !account.archived);
+                    let timestamp = detail.transaction.timestamp;
+                    let transaction = detail.transaction.transaction;
+                    let transaction_link = move |id: Uuid| {
+                        format!("/journal/{}/transaction/{}", journal_id(), id)
+                    };
+                    let controls = (can_append && transaction.reversal.is_none()
+                        && transaction.reverses.is_none())
+                        .then(|| {
+                            view! {
+                                <EntryControls
+                                    journal_id=journal_id()
+                                    transaction_id=transaction.id
+                                    description=transaction.description.clone()
+                                    effective_date=transaction.effective_date
+                                    lines=detail.lines.clone()
+                                    accounts=accounts
+                                    reverse=reverse
+                                    correct=correct
+                                />
+                            }
+                        });
+
                     view! {
-                        <a
-                            href=format!("/journal/{}/transaction/{}", journal_id(), transaction.id)
-                            class="block p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors"
-                        >
-                            <div class="space-y-3">
-                                <div class="space-y-2">
-                                    {transaction
-                                        .entries
-                                        .iter()
-                                        .map(|entry| {
-                                            let entry_amount = format!(
-                                                "${}.{:02}",
-                                                entry.amount / 100,
-                                                entry.amount % 100,
-                                            );
-                                            let entry_type_str = match entry.entry_type {
-                                                EntryType::Debit => "Dr",
-                                                EntryType::Credit => "Cr",
-                                            };
-                                            view! {
+                        <div class="space-y-6">
+                            <div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl space-y-1">
+                                <h2 class="text-lg font-semibold text-gray-900 dark:text-white">
+                                    {if transaction.description.is_empty() {
+                                        "No description".to_string()
+                                    } else {
+                                        transaction.description.clone()
+                                    }}
+                                </h2>
+                                <div class="text-sm text-gray-600 dark:text-gray-400">
+                                    "Effective " {transaction.effective_date.to_string()}
+                                </div>
+                                <div class="text-sm text-gray-600 dark:text-gray-400">
+                                    "Entered by " {transaction.author.clone()} " on "
+                                    {format_time(timestamp)}
+                                </div>
+                            </div>
+
+                            <div class="p-4 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl space-y-2">
+                                {detail
+                                    .lines
+                                    .into_iter()
+                                    .map(|line| {
+                                        let entry_type_str = if line.changed_by < 0 {
+                                            "Cr"
+                                        } else {
+                                            "Dr"
+                                        };
+                                        view! {
+                                            <div>
                                                 <div class="flex justify-between items-center">
                                                     <span class="text-base font-medium text-gray-900 dark:text-white">
-                                                        {entry.account.name.clone()}
+                                                        {line.account_name}
                                                     </span>
                                                     <span class="text-base text-gray-700 dark:text-gray-300">
-                                                        {entry_amount} " " {entry_type_str}
+                                                        {line.currency.format(line.changed_by.abs())} " "
+                                                        {entry_type_str}
                                                     </span>
                                                 </div>
-                                            }
-                                        })
-                                        .collect_view()}
-                                </div>
-                                <div class="text-xs text-gray-400 dark:text-gray-500">
-                                    {transaction.author.username}
-                                </div>
-                            </div>
-                        </a>
-                    }
-                })
-                .collect_view()}
-            <hr class="mt-8 mb-6 border-gray-300 dark:border-gray-600" />
-            <div class="mt-10">
-                <div class="bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-xl p-6">
-                    <h3 class="text-lg font-semibold text-gray-900 dark:text-white mb-6">
-                        "Create New Transaction"
-                    </h3>
-                    <form class="space-y-6">
-                        // Entry 1
-                        <div class="p-4 bg-gray-50 dark:bg-gray-700 rounded-lg space-y-3">
-                            <div>
-                                <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                    "Account"
-                                </label>
-                                <select class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400">
-                                    <option value="">"Select account..."</option>
-                                    <option value="cash">"Cash"</option>
-                                    <option value="checking">"Checking Account"</option>
-                                    <option value="savings">"Savings Account"</option>
-                                    <option value="groceries">"Groceries Expense"</option>
-                                    <option value="fuel">"Fuel Expense"</option>
-                                    <option value="coffee">"Coffee Expense"</option>
-                                </select>
-                            </div>
-                            <div class="grid grid-cols-4 gap-2 sm:gap-3">
-                                <div class="col-span-3">
-                                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                        "Amount"
-                                    </label>
-                                    <input
-                                        type="number"
-                                        step="0.01"
-                                        min="0"
-                                        placeholder="0.00"
-                                        class="w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400 text-right [&::-webkit-outer-spin-button]:appearance-none [&::-webkit-inner-spin-button]:appearance-none [-moz-appearance:textfield]"
-                                    />
-                                </div>
-                                <div>
-                                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                        "Type"
-                                    </label>
-                                    <select class="w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400">
-                                        <option value="debit">"Dr"</option>
-                                        <option value="credit">"Cr"</option>
-                                    </select>
-                                </div>
+                                                {line
+                                                    .memo
+                                                    .map(|memo| {
+                                                        view! {
+                                                            <div class="text-xs text-gray-500 dark:text-gray-400">
+                                                                {memo}
+                                                            </div>
+                                                        }
+                                                    })}
+                                            </div>
+                                        }
+                                    })
+                                    .collect_view()}
+                                {transaction
+                                    .exchange_rates
+                                    .iter()
+                                    .map(|rate| {
+                                        view! {
+                                            <div class="text-xs text-gray-400 dark:text-gray-500">
+                                                {rate.display()}
+                                            </div>
+                                        }
+                                    })
+                                    .collect_view()}
                             </div>
-                        </div>
 
-                        // Entry 2
-                        <div class="p-4 bg-gray-50 dark:bg-gray-700 rounded-lg space-y-3">
-                            <div>
-                                <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                    "Account"
-                                </label>
-                                <select class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400">
-                                    <option value="">"Select account..."</option>
-                                    <option value="cash">"Cash"</option>
-                                    <option value="checking">"Checking Account"</option>
-                                    <option value="savings">"Savings Account"</option>
-                                    <option value="groceries">"Groceries Expense"</option>
-                                    <option value="fuel">"Fuel Expense"</option>
-                                    <option value="coffee">"Coffee Expense"</option>
-                                </select>
-                            </div>
-                            <div class="grid grid-cols-4 gap-2 sm:gap-3">
-                                <div class="col-span-3">
-                                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                        "Amount"
-                                    </label>
-                                    <input
-                                        type="number"
-                                        step="0.01"
-                                        min="0"
-                                        placeholder="0.00"
-                                        class="w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400 text-right [&::-webkit-outer-spin-button]:appearance-none [&::-webkit-inner-spin-button]:appearance-none [-moz-appearance:textfield]"
-                                    />
-                                </div>
-                                <div>
-                                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                        "Type"
-                                    </label>
-                                    <select class="w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400">
-                                        <option value="debit">"Dr"</option>
-                                        <option value="credit">"Cr"</option>
-                                    </select>
-                                </div>
-                            </div>
-                        </div>
+                            {transaction
+                                .corrects
+                                .map(|original| {
+                                    view! {
+                                        <a
+                                            href=transaction_link(original)
+                                            class="block text-sm text-indigo-600 dark:text-indigo-400 hover:underline"
+                                        >
+                                            "Corrects an earlier transaction"
+                                        </a>
+                                    }
+                                })}
 
-                        // Entry 3
-                        <div class="p-4 bg-gray-50 dark:bg-gray-700 rounded-lg space-y-3">
-                            <div>
-                                <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                    "Account (Optional)"
-                                </label>
-                                <select class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400">
-                                    <option value="">"Select account..."</option>
-                                    <option value="cash">"Cash"</option>
-                                    <option value="checking">"Checking Account"</option>
-                                    <option value="savings">"Savings Account"</option>
-                                    <option value="groceries">"Groceries Expense"</option>
-                                    <option value="fuel">"Fuel Expense"</option>
-                                    <option value="coffee">"Coffee Expense"</option>
-                                </select>
-                            </div>
-                            <div class="grid grid-cols-4 gap-2 sm:gap-3">
-                                <div class="col-span-3">
-                                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                        "Amount"
-                                    </label>
-                                    <input
-                                        type="number"
-                                        step="0.01"
-                                        min="0"
-                                        placeholder="0.00"
-                                        class="w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400 text-right [&::-webkit-outer-spin-button]:appearance-none [&::-webkit-inner-spin-button]:appearance-none [-moz-appearance:textfield]"
-                                    />
-                                </div>
-                                <div>
-                                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                        "Type"
-                                    </label>
-                                    <select class="w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400">
-                                        <option value="debit">"Dr"</option>
-                                        <option value="credit">"Cr"</option>
-                                    </select>
-                                </div>
-                            </div>
-                        </div>
+                            {transaction
+                                .reverses
+                                .map(|original| {
+                                    view! {
+                                        <a
+                                            href=transaction_link(original)
+                                            class="block text-sm text-indigo-600 dark:text-indigo-400 hover:underline"
+                                        >
+                                            "Reverses an earlier transaction"
+                                        </a>
+                                    }
+                                })}
 
-                        // Entry 4
-                        <div class="p-4 bg-gray-50 dark:bg-gray-700 rounded-lg space-y-3">
-                            <div>
-                                <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                    "Account (Optional)"
-                                </label>
-                                <select class="w-full rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400">
-                                    <option value="">"Select account..."</option>
-                                    <option value="cash">"Cash"</option>
-                                    <option value="checking">"Checking Account"</option>
-                                    <option value="savings">"Savings Account"</option>
-                                    <option value="groceries">"Groceries Expense"</option>
-                                    <option value="fuel">"Fuel Expense"</option>
-                                    <option value="coffee">"Coffee Expense"</option>
-                                </select>
-                            </div>
-                            <div class="grid grid-cols-4 gap-2 sm:gap-3">
-                                <div class="col-span-3">
-                                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                        "Amount"
-                                    </label>
-                                    <input
-                                        type="number"
-                                        step="0.01"
-                                        min="0"
-                                        placeholder="0.00"
-                                        class="w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white placeholder:text-gray-400 dark:placeholder:text-gray-500 focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400 text-right [&::-webkit-outer-spin-button]:appearance-none [&::-webkit-inner-spin-button]:appearance-none [-moz-appearance:textfield]"
-                                    />
-                                </div>
-                                <div>
-                                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
-                                        "Type"
-                                    </label>
-                                    <select class="w-full h-10 rounded-md border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2 text-gray-900 dark:text-white focus:border-indigo-500 focus:ring-indigo-500 dark:focus:border-indigo-400">
-                                        <option value="debit">"Dr"</option>
-                                        <option value="credit">"Cr"</option>
-                                    </select>
-                                </div>
-                            </div>
-                        </div>
+                            {transaction
+                                .reversal
+                                .map(|reversal| {
+                                    view! {
+                                        <div class="p-4 bg-gray-50 dark:bg-gray-800 rounded-lg space-y-1">
+                                            <div class="text-sm font-medium text-gray-900 dark:text-white">
+                                                "Reversed on " {format_time(reversal.reversed_at)}
+                                            </div>
+                                            <div class="text-sm text-gray-600 dark:text-gray-400">
+                                                {reversal.reason}
+                                            </div>
+                                            {reversal
+                                                .offset
+                                                .map(|offset| {
+                                                    view! {
+                                                        <a
+                                                            href=transaction_link(offset)
+                                                            class="block text-sm text-indigo-600 dark:text-indigo-400 hover:underline"
+                                                        >
+                                                            "View the reversing entry"
+                                                        </a>
+                                                    }
+                                                })}
+                                            {reversal
+                                                .replaced_by
+                                                .map(|replacement| {
+                                                    view! {
+                                                        <a
+                                                            href=transaction_link(replacement)
+                                                            class="block text-sm text-indigo-600 dark:text-indigo-400 hover:underline"
+                                                        >
+                                                            "View the correction"
+                                                        </a>
+                                                    }
+                                                })}
+                                        </div>
+                                    }
+                                })}
 
-                        <div class="flex justify-between items-center pt-4 border-t border-gray-200 dark:border-gray-600">
-                            <div class="text-sm text-gray-500 dark:text-gray-400">
-                                "Debits must equal credits"
-                            </div>
-                            <button
-                                type="submit"
-                                class="px-6 py-2 bg-indigo-600 text-white font-medium rounded-md hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 dark:bg-indigo-500 dark:hover:bg-indigo-400 dark:focus:ring-indigo-400 dark:ring-offset-gray-800"
-                            >
-                                "Create Transaction"
-                            </button>
+                            // outside the controls, so a refusal still shows once the entry is reversed
+                            {ActionError(reverse, "reversing the transaction")}
+                            {ActionError(correct, "correcting the transaction")}
+                            // a reversed entry is final, and so is the offset that reversed it
+                            {controls}
                         </div>
-                    </form>
-                </div>
-            </div>
+                    }
+                        .into_any()
+                })}
+            </Suspense>
         </Layout>
     }
 }